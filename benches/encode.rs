@@ -0,0 +1,47 @@
+//! Compares encoding transactions through an `RlpItem` tree with writing them
+//! directly with `Encodable`
+
+#![allow(dead_code, missing_docs, unused_imports)]
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+
+// tx-util is a binary, so the modules are compiled into the benchmark
+#[path = "../src/quantity.rs"]
+mod quantity;
+#[path = "../src/rlp.rs"]
+mod rlp;
+#[path = "../src/transaction.rs"]
+mod transaction;
+// used by the unit tests of `transaction`, which `--all-targets` compiles
+#[cfg(test)]
+#[path = "../src/strategies.rs"]
+mod strategies;
+
+use rlp::{Encodable, RlpItem};
+use transaction::{Eip1559, Eip7702};
+
+static EIP_1559_SIGNED: &str = include_str!("../transactions/eip1559_signed.json");
+static EIP_7702_SIGNED: &str = include_str!("../transactions/eip7702_signed.json");
+
+fn encode(c: &mut Criterion) {
+    let eip1559: Eip1559 = serde_json::from_str(EIP_1559_SIGNED).unwrap();
+    let eip7702: Eip7702 = serde_json::from_str(EIP_7702_SIGNED).unwrap();
+
+    let mut group = c.benchmark_group("eip1559");
+    group.bench_function("tree", |b| {
+        b.iter(|| Vec::<u8>::from(RlpItem::from(black_box(&eip1559).clone())))
+    });
+    group.bench_function("encodable", |b| b.iter(|| black_box(&eip1559).encoded()));
+    group.finish();
+
+    let mut group = c.benchmark_group("eip7702");
+    group.bench_function("tree", |b| {
+        b.iter(|| Vec::<u8>::from(RlpItem::from(black_box(&eip7702).clone())))
+    });
+    group.bench_function("encodable", |b| b.iter(|| black_box(&eip7702).encoded()));
+    group.finish();
+}
+
+criterion_group!(benches, encode);
+criterion_main!(benches);
@@ -0,0 +1,35 @@
+//! Decodes arbitrary bytes as rlp, which must fail instead of panicking and
+//! encode canonical inputs back to the same bytes
+
+#![no_main]
+#![allow(dead_code, deprecated, unused_imports)]
+
+use libfuzzer_sys::fuzz_target;
+
+// tx-util is a binary, so the modules are compiled into the target
+#[path = "../../src/rlp.rs"]
+mod rlp;
+
+use rlp::{Encodable, RlpItem, RlpReader, RlpView};
+
+/// Whether every header in `view` is the shortest encoding of its payload
+fn canonical(view: RlpView<'_>) -> bool {
+    view.is_canonical() && view.items().all(|item| item.is_ok_and(canonical))
+}
+
+fuzz_target!(|data: &[u8]| {
+    for item in RlpReader::new(data) {
+        if item.is_err() {
+            break;
+        }
+    }
+
+    let Ok(item) = RlpItem::decode(data) else {
+        return;
+    };
+    let view = RlpView::parse_exact(data).expect("decoded above");
+    if canonical(view) {
+        assert_eq!(item.encoded(), data);
+        assert_eq!(Vec::<u8>::from(item), data);
+    }
+});
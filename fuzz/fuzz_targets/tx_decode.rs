@@ -0,0 +1,41 @@
+//! Decodes arbitrary bytes as a typed transaction, which must fail instead of
+//! panicking and encode every decoded transaction back to the same bytes
+
+#![no_main]
+#![allow(dead_code, deprecated, unused_imports)]
+
+use libfuzzer_sys::fuzz_target;
+
+// tx-util is a binary, so the modules are compiled into the target
+#[path = "../../src/quantity.rs"]
+mod quantity;
+#[path = "../../src/rlp.rs"]
+mod rlp;
+#[path = "../../src/transaction.rs"]
+mod transaction;
+
+use rlp::Decodable;
+use transaction::{Eip1559, Eip7702};
+
+fuzz_target!(|data: &[u8]| {
+    let Some((tx_type, payload)) = data.split_first() else {
+        return;
+    };
+    let encoded = match tx_type {
+        0x2 => Eip1559::decode_bytes(payload).map(|tx| {
+            let _ = tx.sender();
+            tx.encode_typed()
+        }),
+        0x4 => Eip7702::decode_bytes(payload).map(|tx| {
+            let _ = tx.sender();
+            for auth in &tx.authorization_list {
+                let _ = auth.authority();
+            }
+            tx.encode_typed()
+        }),
+        _ => return,
+    };
+    if let Ok(encoded) = encoded {
+        assert_eq!(encoded, data);
+    }
+});
@@ -0,0 +1,65 @@
+//! Builds the seed corpus of the fuzz targets from `transactions/*.json`.
+//!
+//! Run from the `fuzz` directory with `cargo run --bin seed_corpus`. Each
+//! transaction is encoded as is, signed or not, with a type 4 transaction in
+//! both authorization revisions.
+
+#![allow(dead_code, deprecated, unused_imports)]
+
+use std::{fs, path::Path};
+
+#[path = "../src/quantity.rs"]
+mod quantity;
+#[path = "../src/rlp.rs"]
+mod rlp;
+#[path = "../src/transaction.rs"]
+mod transaction;
+
+use serde_json::Value;
+use transaction::{Eip1559, Eip7702, Eip7702Revision};
+
+fn main() -> color_eyre::Result<()> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut seeds = Vec::new();
+    let mut entries = fs::read_dir(root.join("../transactions"))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+    for path in entries {
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let stem = path.file_stem().unwrap().to_string_lossy().to_string();
+        let json: Value = serde_json::from_str(&fs::read_to_string(&path)?)?;
+        if json.get("authorizationList").is_some() {
+            let Ok(tx) = serde_json::from_value::<Eip7702>(json) else {
+                continue;
+            };
+            seeds.push((format!("{stem}_draft"), tx.clone().encode_typed()));
+            if let Ok(tx) = tx.with_revision(Eip7702Revision::Prague) {
+                seeds.push((format!("{stem}_prague"), tx.encode_typed()));
+            }
+        } else if let Ok(tx) = serde_json::from_value::<Eip1559>(json) {
+            seeds.push((stem, tx.encode_typed()));
+        }
+    }
+
+    for target in ["rlp_decode", "tx_decode"] {
+        let dir = root.join("corpus").join(target);
+        fs::create_dir_all(&dir)?;
+        for (name, bytes) in &seeds {
+            // the rlp target also gets the payload without the type byte
+            let bytes = match target {
+                "rlp_decode" => &bytes[1..],
+                _ => bytes.as_slice(),
+            };
+            fs::write(dir.join(name), bytes)?;
+        }
+    }
+    println!(
+        "wrote {} seeds to {}",
+        seeds.len(),
+        root.join("corpus").display()
+    );
+    Ok(())
+}
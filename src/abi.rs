@@ -0,0 +1,933 @@
+use alloy_primitives::{Address, Bytes, U256};
+use color_eyre::eyre::{eyre, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha3::{Digest, Keccak256};
+use std::{fmt, fs, path::Path, str::FromStr};
+
+/// A [Solidity ABI](https://docs.soliditylang.org/en/latest/abi-spec.html) type
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum AbiType {
+    Uint(usize),
+    Int(usize),
+    Address,
+    Bool,
+    FixedBytes(usize),
+    Bytes,
+    String,
+    Array(Box<AbiType>),
+    FixedArray(Box<AbiType>, usize),
+    Tuple(Vec<AbiType>),
+}
+
+/// A function call given as a signature and its arguments
+/// ```json
+/// {
+///   "signature": "transfer(address,uint256)",
+///   "args": ["0x5a96834046c1dff63119eb0eed6330fc5007a1d7", "7000000000"]
+/// }
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct Call {
+    pub(crate) signature: String,
+    #[serde(default)]
+    pub(crate) args: Vec<Value>,
+}
+
+/// A parsed function signature, e.g. `transfer(address,uint256)`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Function {
+    pub(crate) name: String,
+    pub(crate) inputs: Vec<AbiType>,
+}
+
+impl fmt::Display for AbiType {
+    /// Formats the canonical type used in function selectors
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AbiType::Uint(bits) => write!(f, "uint{bits}"),
+            AbiType::Int(bits) => write!(f, "int{bits}"),
+            AbiType::Address => write!(f, "address"),
+            AbiType::Bool => write!(f, "bool"),
+            AbiType::FixedBytes(size) => write!(f, "bytes{size}"),
+            AbiType::Bytes => write!(f, "bytes"),
+            AbiType::String => write!(f, "string"),
+            AbiType::Array(inner) => write!(f, "{inner}[]"),
+            AbiType::FixedArray(inner, size) => write!(f, "{inner}[{size}]"),
+            AbiType::Tuple(types) => {
+                write!(f, "(")?;
+                for (i, ty) in types.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{ty}")?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+impl FromStr for AbiType {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if let Some(rest) = s.strip_suffix(']') {
+            let open = rest
+                .rfind('[')
+                .ok_or_else(|| eyre!("invalid abi type `{s}`"))?;
+            let inner = Box::new(rest[..open].parse()?);
+            return match &rest[open + 1..] {
+                "" => Ok(AbiType::Array(inner)),
+                size => Ok(AbiType::FixedArray(
+                    inner,
+                    size.parse()
+                        .map_err(|_| eyre!("invalid array size in `{s}`"))?,
+                )),
+            };
+        }
+        if let Some(inner) = s
+            .strip_prefix("tuple")
+            .unwrap_or(s)
+            .strip_prefix('(')
+            .and_then(|s| s.strip_suffix(')'))
+        {
+            return Ok(AbiType::Tuple(
+                split_top_level(inner)?
+                    .into_iter()
+                    .map(str::parse)
+                    .collect::<Result<_>>()?,
+            ));
+        }
+
+        let sized = |prefix: &str, max: usize, step: usize| -> Option<Result<usize>> {
+            let size = s.strip_prefix(prefix)?;
+            Some(match size.parse::<usize>() {
+                Ok(n) if n > 0 && n <= max && n % step == 0 => Ok(n),
+                _ => Err(eyre!("invalid abi type `{s}`")),
+            })
+        };
+        match s {
+            "address" => Ok(AbiType::Address),
+            "bool" => Ok(AbiType::Bool),
+            "string" => Ok(AbiType::String),
+            "bytes" => Ok(AbiType::Bytes),
+            "uint" => Ok(AbiType::Uint(256)),
+            "int" => Ok(AbiType::Int(256)),
+            _ => {
+                if let Some(bits) = sized("uint", 256, 8) {
+                    Ok(AbiType::Uint(bits?))
+                } else if let Some(bits) = sized("int", 256, 8) {
+                    Ok(AbiType::Int(bits?))
+                } else if let Some(size) = sized("bytes", 32, 1) {
+                    Ok(AbiType::FixedBytes(size?))
+                } else {
+                    Err(eyre!("invalid abi type `{s}`"))
+                }
+            }
+        }
+    }
+}
+
+/// Splits a comma separated list of types or values, ignoring commas nested
+/// in brackets or parentheses
+fn split_top_level(s: &str) -> Result<Vec<&str>> {
+    if s.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or_else(|| eyre!("unbalanced brackets in `{s}`"))?
+            }
+            ',' if depth == 0 => {
+                parts.push(s[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        Err(eyre!("unbalanced brackets in `{s}`"))?;
+    }
+    parts.push(s[start..].trim());
+    Ok(parts)
+}
+
+impl FromStr for Function {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim().strip_prefix("function ").unwrap_or(s.trim());
+        let open = s
+            .find('(')
+            .ok_or_else(|| eyre!("invalid function signature `{s}`"))?;
+        let name = s[..open].trim();
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+        {
+            Err(eyre!("invalid function name in `{s}`"))?;
+        }
+        match s[open..].parse()? {
+            AbiType::Tuple(inputs) => Ok(Function {
+                name: name.to_string(),
+                inputs,
+            }),
+            _ => Err(eyre!("invalid function signature `{s}`")),
+        }
+    }
+}
+
+impl Function {
+    /// The canonical signature, e.g. `transfer(address,uint256)`
+    pub(crate) fn signature(&self) -> String {
+        format!("{}{}", self.name, AbiType::Tuple(self.inputs.clone()))
+    }
+
+    /// The first four bytes of the keccak256 hash of the canonical signature
+    pub(crate) fn selector(&self) -> [u8; 4] {
+        let hash = Keccak256::digest(self.signature().as_bytes());
+        [hash[0], hash[1], hash[2], hash[3]]
+    }
+
+    /// Encodes the selector followed by the abi encoded `args`
+    pub(crate) fn encode(&self, args: &[Value]) -> Result<Bytes> {
+        if args.len() != self.inputs.len() {
+            Err(eyre!(
+                "`{}` takes {} arguments but {} were supplied",
+                self.signature(),
+                self.inputs.len(),
+                args.len()
+            ))?;
+        }
+        let mut data = self.selector().to_vec();
+        data.append(&mut encode_tuple(&self.inputs, args)?);
+        Ok(data.into())
+    }
+
+    /// Decodes abi encoded arguments, without the selector
+    pub(crate) fn decode(&self, args: &[u8]) -> Result<Vec<Value>> {
+        decode_tuple(&self.inputs, args)
+    }
+}
+
+/// A set of known functions to decode calldata against
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Abi {
+    pub(crate) functions: Vec<Function>,
+}
+
+/// A parameter of a function in a json ABI
+#[derive(Deserialize)]
+struct JsonParam {
+    #[serde(rename = "type")]
+    ty: String,
+    #[serde(default)]
+    components: Vec<JsonParam>,
+}
+
+/// An item in a json ABI, only functions are used
+#[derive(Deserialize)]
+struct JsonItem {
+    #[serde(rename = "type", default)]
+    kind: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    inputs: Vec<JsonParam>,
+}
+
+impl JsonParam {
+    fn abi_type(&self) -> Result<AbiType> {
+        match self.ty.strip_prefix("tuple") {
+            Some(suffix) => {
+                let components = self
+                    .components
+                    .iter()
+                    .map(|c| c.abi_type().map(|ty| ty.to_string()))
+                    .collect::<Result<Vec<_>>>()?;
+                format!("({}){suffix}", components.join(",")).parse()
+            }
+            None => self.ty.parse(),
+        }
+    }
+}
+
+impl Abi {
+    /// Loads a json ABI, a compiler artifact with an `abi` field, or a list
+    /// of function signatures with one signature per line
+    pub(crate) fn load(path: &Path) -> Result<Self> {
+        let input = fs::read_to_string(path)
+            .map_err(|e| eyre!("unable to read abi `{}`: {e}", path.display()))?;
+        let trimmed = input.trim_start();
+        if trimmed.starts_with('[') || trimmed.starts_with('{') {
+            Abi::from_json(&input)
+        } else {
+            Abi::from_signatures(
+                input
+                    .lines()
+                    .map(str::trim)
+                    .filter(|l| !l.is_empty() && !l.starts_with('#') && !l.starts_with("//")),
+            )
+        }
+    }
+
+    pub(crate) fn from_json(input: &str) -> Result<Self> {
+        let mut value: Value = serde_json::from_str(input)?;
+        if let Some(abi) = value.get_mut("abi") {
+            value = abi.take();
+        }
+        let items: Vec<JsonItem> = serde_json::from_value(value)?;
+        let functions = items
+            .into_iter()
+            .filter(|item| item.kind == "function")
+            .map(|item| {
+                Ok(Function {
+                    name: item.name,
+                    inputs: item
+                        .inputs
+                        .iter()
+                        .map(JsonParam::abi_type)
+                        .collect::<Result<_>>()?,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Abi { functions })
+    }
+
+    pub(crate) fn from_signatures<'a>(
+        signatures: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Self> {
+        let functions = signatures
+            .into_iter()
+            .map(str::parse)
+            .collect::<Result<_>>()?;
+        Ok(Abi { functions })
+    }
+
+    pub(crate) fn extend(&mut self, other: Abi) {
+        self.functions.extend(other.functions);
+    }
+
+    /// Decodes calldata against the known functions
+    ///
+    /// Returns the matched function and its arguments, or an `error` for
+    /// unknown selectors and arguments that do not decode.
+    pub(crate) fn decode_call(&self, data: &[u8]) -> Value {
+        if data.len() < 4 {
+            return json!({ "error": "data is shorter than a selector" });
+        }
+        let (selector, args) = data.split_at(4);
+        let mut candidates = self
+            .functions
+            .iter()
+            .filter(|f| f.selector() == selector)
+            .peekable();
+        if candidates.peek().is_none() {
+            return json!({
+                "selector": format!("0x{}", hex::encode(selector)),
+                "error": "unknown selector",
+            });
+        }
+        let mut result = Value::Null;
+        for function in candidates {
+            result = match function.decode(args) {
+                Ok(args) => {
+                    return json!({
+                        "function": function.name,
+                        "signature": function.signature(),
+                        "args": args,
+                    })
+                }
+                Err(e) => json!({
+                    "function": function.name,
+                    "signature": function.signature(),
+                    "error": e.to_string(),
+                }),
+            };
+        }
+        result
+    }
+}
+
+impl Call {
+    pub(crate) fn encode(&self) -> Result<Bytes> {
+        self.signature.parse::<Function>()?.encode(&self.args)
+    }
+}
+
+/// Replaces a `call` object in the transaction input with the encoded `data`
+pub(crate) fn expand_call(input: &mut Value) -> Result<()> {
+    let Some(input) = input.as_object_mut() else {
+        return Ok(());
+    };
+    let Some(call) = input.remove("call") else {
+        return Ok(());
+    };
+    if input.contains_key("data") {
+        Err(eyre!(
+            "the transaction input may contain `call` or `data`, not both"
+        ))?;
+    }
+    let call: Call = serde_json::from_value(call)?;
+    let _ = input.insert("data".into(), serde_json::to_value(call.encode()?)?);
+    Ok(())
+}
+
+impl AbiType {
+    pub(crate) fn is_dynamic(&self) -> bool {
+        match self {
+            AbiType::Bytes | AbiType::String | AbiType::Array(_) => true,
+            AbiType::FixedArray(inner, _) => inner.is_dynamic(),
+            AbiType::Tuple(types) => types.iter().any(AbiType::is_dynamic),
+            _ => false,
+        }
+    }
+
+    /// The size of the head of a value of this type
+    pub(crate) fn head_size(&self) -> usize {
+        match self {
+            _ if self.is_dynamic() => 32,
+            // saturates for sizes that cannot fit in any calldata
+            AbiType::FixedArray(inner, size) => inner.head_size().saturating_mul(*size),
+            AbiType::Tuple(types) => types
+                .iter()
+                .fold(0, |size, ty| size.saturating_add(ty.head_size())),
+            _ => 32,
+        }
+    }
+}
+
+fn word(value: U256) -> Vec<u8> {
+    value.to_be_bytes::<32>().to_vec()
+}
+
+fn pad_right(mut bytes: Vec<u8>) -> Vec<u8> {
+    bytes.resize(bytes.len().div_ceil(32) * 32, 0);
+    bytes
+}
+
+/// Encodes `values` as a tuple of `types`, dynamic values are appended after
+/// the heads and referenced by their offset
+fn encode_tuple(types: &[AbiType], values: &[Value]) -> Result<Vec<u8>> {
+    let mut head = Vec::new();
+    let mut tail = Vec::new();
+    let head_size: usize = types.iter().map(AbiType::head_size).sum();
+    for (ty, value) in types.iter().zip(values) {
+        let mut encoded = encode(ty, value)?;
+        if ty.is_dynamic() {
+            head.append(&mut word(U256::from(head_size + tail.len())));
+            tail.append(&mut encoded);
+        } else {
+            head.append(&mut encoded);
+        }
+    }
+    head.append(&mut tail);
+    Ok(head)
+}
+
+/// Values for lists and tuples are json arrays, or strings like `[1,2]`
+/// as they would be passed on the command line
+fn list_items(ty: &AbiType, value: &Value) -> Result<Vec<Value>> {
+    match value {
+        Value::Array(items) => Ok(items.clone()),
+        Value::String(s) => {
+            let s = s.trim();
+            let inner = s
+                .strip_prefix('[')
+                .and_then(|s| s.strip_suffix(']'))
+                .or_else(|| s.strip_prefix('(').and_then(|s| s.strip_suffix(')')))
+                .ok_or_else(|| eyre!("expected a list for `{ty}`, got `{s}`"))?;
+            Ok(split_top_level(inner)?
+                .into_iter()
+                .map(|item| serde_json::from_str(item).unwrap_or(Value::String(item.into())))
+                .collect())
+        }
+        _ => Err(eyre!("expected a list for `{ty}`, got `{value}`")),
+    }
+}
+
+fn hex_bytes(ty: &AbiType, value: &Value) -> Result<Vec<u8>> {
+    let s = value
+        .as_str()
+        .ok_or_else(|| eyre!("expected a hex string for `{ty}`, got `{value}`"))?;
+    hex::decode(s.trim_start_matches("0x")).map_err(|e| eyre!("invalid `{ty}` value `{s}`: {e}"))
+}
+
+/// Parses an integer from a json number, or a decimal or `0x` prefixed
+/// string. Returns the magnitude and whether it is negative.
+fn integer(ty: &AbiType, value: &Value) -> Result<(U256, bool)> {
+    let invalid = || eyre!("invalid `{ty}` value `{value}`");
+    match value {
+        Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(n), _) => Ok((U256::from(n), false)),
+            (None, Some(n)) => Ok((U256::from(n.unsigned_abs()), true)),
+            _ => Err(invalid()),
+        },
+        Value::String(s) => {
+            let (s, negative) = match s.trim().strip_prefix('-') {
+                Some(s) => (s, true),
+                None => (s.trim(), false),
+            };
+            let magnitude = U256::from_str(s).map_err(|_| invalid())?;
+            Ok((magnitude, negative && magnitude != U256::ZERO))
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// Encodes a single value, see [`encode_tuple`] for how it is placed
+fn encode(ty: &AbiType, value: &Value) -> Result<Vec<u8>> {
+    let out_of_range = || eyre!("`{value}` is out of range for `{ty}`");
+    match ty {
+        AbiType::Uint(bits) => {
+            let (value, negative) = integer(ty, value)?;
+            if negative || (*bits < 256 && value.bit_len() > *bits) {
+                Err(out_of_range())?;
+            }
+            Ok(word(value))
+        }
+        AbiType::Int(bits) => {
+            let (magnitude, negative) = integer(ty, value)?;
+            let limit = U256::from(1) << (bits - 1);
+            if (negative && magnitude > limit) || (!negative && magnitude >= limit) {
+                Err(out_of_range())?;
+            }
+            // two's complement
+            Ok(word(if negative {
+                magnitude.wrapping_neg()
+            } else {
+                magnitude
+            }))
+        }
+        AbiType::Address => {
+            let address = value
+                .as_str()
+                .and_then(|s| s.trim().parse::<Address>().ok())
+                .ok_or_else(|| eyre!("invalid `address` value `{value}`"))?;
+            let mut encoded = vec![0u8; 12];
+            encoded.extend_from_slice(address.as_slice());
+            Ok(encoded)
+        }
+        AbiType::Bool => match value {
+            Value::Bool(b) => Ok(word(U256::from(*b as u8))),
+            Value::String(s) if s == "true" || s == "false" => {
+                Ok(word(U256::from((s == "true") as u8)))
+            }
+            _ => Err(eyre!("invalid `bool` value `{value}`")),
+        },
+        AbiType::FixedBytes(size) => {
+            let bytes = hex_bytes(ty, value)?;
+            if bytes.len() != *size {
+                Err(eyre!("`{ty}` requires {size} bytes, got {}", bytes.len()))?;
+            }
+            Ok(pad_right(bytes))
+        }
+        AbiType::Bytes => {
+            let bytes = hex_bytes(ty, value)?;
+            let mut encoded = word(U256::from(bytes.len()));
+            encoded.append(&mut pad_right(bytes));
+            Ok(encoded)
+        }
+        AbiType::String => {
+            let s = value
+                .as_str()
+                .ok_or_else(|| eyre!("invalid `string` value `{value}`"))?;
+            let mut encoded = word(U256::from(s.len()));
+            encoded.append(&mut pad_right(s.as_bytes().to_vec()));
+            Ok(encoded)
+        }
+        AbiType::Array(inner) => {
+            let items = list_items(ty, value)?;
+            let types = vec![*inner.clone(); items.len()];
+            let mut encoded = word(U256::from(items.len()));
+            encoded.append(&mut encode_tuple(&types, &items)?);
+            Ok(encoded)
+        }
+        AbiType::FixedArray(inner, size) => {
+            let items = list_items(ty, value)?;
+            if items.len() != *size {
+                Err(eyre!("`{ty}` requires {size} items, got {}", items.len()))?;
+            }
+            encode_tuple(&vec![*inner.clone(); *size], &items)
+        }
+        AbiType::Tuple(types) => {
+            let items = list_items(ty, value)?;
+            if items.len() != types.len() {
+                Err(eyre!(
+                    "`{ty}` requires {} items, got {}",
+                    types.len(),
+                    items.len()
+                ))?;
+            }
+            encode_tuple(types, &items)
+        }
+    }
+}
+
+/// Reads the 32 byte word at `offset`
+fn word_at(data: &[u8], offset: usize) -> Result<&[u8]> {
+    offset
+        .checked_add(32)
+        .and_then(|end| data.get(offset..end))
+        .ok_or_else(|| eyre!("unexpected end of data at offset {offset}"))
+}
+
+/// Reads an offset or length, which must fit in `usize`
+fn usize_at(data: &[u8], offset: usize) -> Result<usize> {
+    let value = U256::from_be_slice(word_at(data, offset)?);
+    usize::try_from(value).map_err(|_| eyre!("invalid offset or length {value}"))
+}
+
+fn integer_value(value: U256, negative: bool) -> Value {
+    match (u64::try_from(value), negative) {
+        (Ok(n), false) => json!(n),
+        (Ok(n), true) if n <= i64::MAX as u64 + 1 => json!((n as i64).wrapping_neg()),
+        (_, false) => json!(value.to_string()),
+        (_, true) => json!(format!("-{value}")),
+    }
+}
+
+/// Decodes `types` from the tuple encoding `data`, see [`encode_tuple`]
+fn decode_tuple(types: &[AbiType], data: &[u8]) -> Result<Vec<Value>> {
+    let mut values = Vec::new();
+    let mut offset = 0;
+    for ty in types {
+        let value = if ty.is_dynamic() {
+            let start = usize_at(data, offset)?;
+            let tail = data
+                .get(start..)
+                .ok_or_else(|| eyre!("offset {start} is out of bounds"))?;
+            decode(ty, tail)?
+        } else {
+            decode(
+                ty,
+                data.get(offset..)
+                    .ok_or_else(|| eyre!("unexpected end of data at offset {offset}"))?,
+            )?
+        };
+        values.push(value);
+        offset += ty.head_size();
+    }
+    Ok(values)
+}
+
+/// Decodes a single value at the start of `data`
+fn decode(ty: &AbiType, data: &[u8]) -> Result<Value> {
+    let invalid = || eyre!("invalid `{ty}` value");
+    match ty {
+        AbiType::Uint(bits) => {
+            let value = U256::from_be_slice(word_at(data, 0)?);
+            if value.bit_len() > *bits {
+                Err(invalid())?;
+            }
+            Ok(integer_value(value, false))
+        }
+        AbiType::Int(bits) => {
+            let value = U256::from_be_slice(word_at(data, 0)?);
+            let negative = value.bit(255);
+            let magnitude = if negative {
+                value.wrapping_neg()
+            } else {
+                value
+            };
+            let limit = U256::from(1) << (bits - 1);
+            if (negative && magnitude > limit) || (!negative && magnitude >= limit) {
+                Err(invalid())?;
+            }
+            Ok(integer_value(magnitude, negative))
+        }
+        AbiType::Address => {
+            let word = word_at(data, 0)?;
+            if word[..12].iter().any(|b| *b != 0) {
+                Err(invalid())?;
+            }
+            Ok(json!(Address::from_slice(&word[12..]).to_checksum(None)))
+        }
+        AbiType::Bool => match U256::from_be_slice(word_at(data, 0)?) {
+            v if v == U256::ZERO => Ok(json!(false)),
+            v if v == U256::from(1) => Ok(json!(true)),
+            _ => Err(invalid()),
+        },
+        AbiType::FixedBytes(size) => {
+            let word = word_at(data, 0)?;
+            if word[*size..].iter().any(|b| *b != 0) {
+                Err(invalid())?;
+            }
+            Ok(json!(format!("0x{}", hex::encode(&word[..*size]))))
+        }
+        AbiType::Bytes | AbiType::String => {
+            let len = usize_at(data, 0)?;
+            let bytes = len
+                .checked_add(32)
+                .and_then(|end| data.get(32..end))
+                .ok_or_else(|| eyre!("`{ty}` of length {len} is out of bounds"))?;
+            match ty {
+                AbiType::String => Ok(json!(String::from_utf8(bytes.to_vec())
+                    .map_err(|_| eyre!("`string` is not valid utf-8"))?)),
+                _ => Ok(json!(format!("0x{}", hex::encode(bytes)))),
+            }
+        }
+        AbiType::Array(inner) => {
+            let len = usize_at(data, 0)?;
+            // every item takes at least one word, which bounds the length
+            if len > data.len() / 32 {
+                Err(eyre!("`{ty}` of length {len} is out of bounds"))?;
+            }
+            Ok(Value::Array(decode_tuple(
+                &vec![*inner.clone(); len],
+                &data[32..],
+            )?))
+        }
+        AbiType::FixedArray(inner, size) => {
+            // the heads of the items must fit in the data, an item without a
+            // head is counted as one byte to bound the size all the same
+            if inner.head_size().max(1).saturating_mul(*size) > data.len() {
+                Err(eyre!("`{ty}` is out of bounds"))?;
+            }
+            Ok(Value::Array(decode_tuple(
+                &vec![*inner.clone(); *size],
+                data,
+            )?))
+        }
+        AbiType::Tuple(types) => Ok(Value::Array(decode_tuple(types, data)?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn encode_call(signature: &str, args: Value) -> String {
+        let call = Call {
+            signature: signature.into(),
+            args: serde_json::from_value(args).unwrap(),
+        };
+        hex::encode(call.encode().unwrap())
+    }
+
+    #[test]
+    fn parses_types() {
+        let f: Function = "f(uint, (int8,bytes32)[2][], tuple(string,bool))"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            f.signature(),
+            "f(uint256,(int8,bytes32)[2][],(string,bool))"
+        );
+        assert!("uint7".parse::<AbiType>().is_err());
+        assert!("uint264".parse::<AbiType>().is_err());
+        assert!("bytes33".parse::<AbiType>().is_err());
+        assert!("(uint256".parse::<AbiType>().is_err());
+        assert!("f(uint256".parse::<Function>().is_err());
+        assert!("(uint256)".parse::<Function>().is_err());
+    }
+
+    #[test]
+    fn encodes_transfer() {
+        assert_eq!(
+            encode_call(
+                "transfer(address,uint256)",
+                json!(["0x5a96834046c1dff63119eb0eed6330fc5007a1d7", "0x1a1432720"])
+            ),
+            "a9059cbb0000000000000000000000005a96834046c1dff63119eb0eed6330fc5007a1d700000000000000000000000000000000000000000000000000000001a1432720"
+        );
+    }
+
+    #[test]
+    fn encodes_dynamic_types() {
+        // examples from the abi spec
+        assert_eq!(
+            encode_call(
+                "f(uint256,uint32[],bytes10,bytes)",
+                json!([
+                    "0x123",
+                    ["0x456", "0x789"],
+                    "0x31323334353637383930",
+                    format!("0x{}", hex::encode("Hello, world!"))
+                ])
+            ),
+            concat!(
+                "8be65246",
+                "0000000000000000000000000000000000000000000000000000000000000123",
+                "0000000000000000000000000000000000000000000000000000000000000080",
+                "3132333435363738393000000000000000000000000000000000000000000000",
+                "00000000000000000000000000000000000000000000000000000000000000e0",
+                "0000000000000000000000000000000000000000000000000000000000000002",
+                "0000000000000000000000000000000000000000000000000000000000000456",
+                "0000000000000000000000000000000000000000000000000000000000000789",
+                "000000000000000000000000000000000000000000000000000000000000000d",
+                "48656c6c6f2c20776f726c642100000000000000000000000000000000000000",
+            )
+        );
+        assert_eq!(
+            encode_call(
+                "g(uint256[][],string[])",
+                json!(["[[1,2],[3]]", ["one", "two", "three"]])
+            ),
+            concat!(
+                "2289b18c",
+                "0000000000000000000000000000000000000000000000000000000000000040",
+                "0000000000000000000000000000000000000000000000000000000000000140",
+                "0000000000000000000000000000000000000000000000000000000000000002",
+                "0000000000000000000000000000000000000000000000000000000000000040",
+                "00000000000000000000000000000000000000000000000000000000000000a0",
+                "0000000000000000000000000000000000000000000000000000000000000002",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "0000000000000000000000000000000000000000000000000000000000000002",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "0000000000000000000000000000000000000000000000000000000000000003",
+                "0000000000000000000000000000000000000000000000000000000000000003",
+                "0000000000000000000000000000000000000000000000000000000000000060",
+                "00000000000000000000000000000000000000000000000000000000000000a0",
+                "00000000000000000000000000000000000000000000000000000000000000e0",
+                "0000000000000000000000000000000000000000000000000000000000000003",
+                "6f6e650000000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000003",
+                "74776f0000000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000005",
+                "7468726565000000000000000000000000000000000000000000000000000000",
+            )
+        );
+    }
+
+    #[test]
+    fn encodes_tuples_and_ints() {
+        assert_eq!(
+            encode_call("h((int8,bool),int256)", json!([[-1, true], "-2"])),
+            concat!(
+                "59a9d485",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe",
+            )
+        );
+    }
+
+    #[test]
+    fn decodes_calls() {
+        let abi = Abi::from_signatures([
+            "transfer(address,uint256)",
+            "g(uint256[][],string[])",
+            "h((int8,bool),int256)",
+        ])
+        .unwrap();
+        for (signature, args) in [
+            (
+                "transfer(address,uint256)",
+                json!(["0x5a96834046c1DFf63119eB0eed6330fc5007a1d7", 7000500000u64]),
+            ),
+            (
+                "g(uint256[][],string[])",
+                json!([[[1, 2], [3]], ["one", "two", "three"]]),
+            ),
+            (
+                "h((int8,bool),int256)",
+                json!([[-1, true], "-57896044618658097711785492504343953926634992332820282019728792003956564819968"]),
+            ),
+        ] {
+            let data = signature
+                .parse::<Function>()
+                .unwrap()
+                .encode(args.as_array().unwrap())
+                .unwrap();
+            let decoded = abi.decode_call(&data);
+            assert_eq!(decoded["signature"], json!(signature));
+            assert_eq!(decoded["args"], args);
+        }
+
+        let unknown = abi.decode_call(&hex::decode("12345678").unwrap());
+        assert_eq!(unknown["selector"], json!("0x12345678"));
+        assert_eq!(unknown["error"], json!("unknown selector"));
+
+        // truncated arguments
+        let data =
+            hex::decode("a9059cbb0000000000000000000000005a96834046c1dff63119eb0eed6330fc5007a1d7")
+                .unwrap();
+        assert!(abi.decode_call(&data)["error"].is_string());
+
+        // an array length that does not fit the data
+        let data = hex::decode(concat!(
+            "2289b18c",
+            "0000000000000000000000000000000000000000000000000000000000000040",
+            "0000000000000000000000000000000000000000000000000000000000000060",
+            "00000000000000000000000000000000000000000000000000000000ffffffff",
+        ))
+        .unwrap();
+        assert!(abi.decode_call(&data)["error"].is_string());
+
+        // fixed array sizes that do not fit the data
+        for signature in [
+            "f(uint256[4294967295])",
+            "f(uint256[][4294967295])",
+            "f(()[4294967295])",
+            "f(uint256[4294967296][4294967296])",
+        ] {
+            let f: Function = signature.parse().unwrap();
+            assert!(f.decode(&[0; 64]).is_err(), "{signature}");
+        }
+        let f: Function = "f(uint256[2])".parse().unwrap();
+        assert_eq!(f.decode(&[0; 64]).unwrap(), vec![json!([0, 0])]);
+    }
+
+    #[test]
+    fn loads_json_abi() {
+        let abi = Abi::from_json(
+            r#"{
+                "abi": [
+                    { "type": "constructor", "inputs": [] },
+                    {
+                        "type": "function",
+                        "name": "execute",
+                        "inputs": [
+                            {
+                                "name": "calls",
+                                "type": "tuple[]",
+                                "components": [
+                                    { "name": "to", "type": "address" },
+                                    { "name": "value", "type": "uint256" },
+                                    { "name": "data", "type": "bytes" }
+                                ]
+                            }
+                        ],
+                        "outputs": []
+                    },
+                    { "type": "event", "name": "Executed", "inputs": [] }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(abi.functions.len(), 1);
+        assert_eq!(
+            abi.functions[0].signature(),
+            "execute((address,uint256,bytes)[])"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        let f: Function = "f(uint8,int8)".parse().unwrap();
+        assert!(f.encode(&[json!(255), json!(-128)]).is_ok());
+        assert!(f.encode(&[json!(256), json!(0)]).is_err());
+        assert!(f.encode(&[json!(-1), json!(0)]).is_err());
+        assert!(f.encode(&[json!(0), json!(128)]).is_err());
+        assert!(f.encode(&[json!(0), json!(-129)]).is_err());
+        assert!(f.encode(&[json!(0)]).is_err());
+
+        let f: Function = "f(bytes4,address)".parse().unwrap();
+        assert!(f
+            .encode(&[json!("0x123456"), json!(Address::ZERO)])
+            .is_err());
+        assert!(f.encode(&[json!("0x12345678"), json!("0x1234")]).is_err());
+    }
+}
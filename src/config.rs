@@ -0,0 +1,194 @@
+use crate::{fork::Fork, input::InputFormat, quantity, transaction::Eip7702Revision};
+use alloy_primitives::{U256, U64};
+use color_eyre::eyre::{eyre, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, env, fs, path::Path, path::PathBuf};
+
+/// Name of the project-local config file, looked up in the working directory
+const LOCAL_CONFIG: &str = "tx-util.toml";
+
+/// A config file with named profiles
+/// ```toml
+/// [profiles.devnet]
+/// chainId = 1337
+/// maxFeePerGas = "0x3b9aca00"
+/// maxPriorityFeePerGas = 1000000
+/// gasLimit = 100000
+/// signer = { env = "DEVNET_KEY" }
+/// eip7702Revision = "prague"
+/// fork = "prague"
+/// rpcUrl = "http://127.0.0.1:8545"
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Config {
+    #[serde(default)]
+    pub(crate) profiles: BTreeMap<String, Profile>,
+}
+
+/// Defaults applied to every transaction encoded with this profile
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub(crate) struct Profile {
+    #[serde(default, deserialize_with = "quantity::option")]
+    pub(crate) chain_id: Option<U64>,
+    #[serde(default, deserialize_with = "quantity::option")]
+    pub(crate) max_fee_per_gas: Option<U256>,
+    #[serde(default, deserialize_with = "quantity::option")]
+    pub(crate) max_priority_fee_per_gas: Option<U256>,
+    #[serde(default, deserialize_with = "quantity::option")]
+    pub(crate) gas_limit: Option<U64>,
+    pub(crate) signer: Option<SignerSource>,
+    pub(crate) eip7702_revision: Option<Eip7702Revision>,
+    pub(crate) fork: Option<Fork>,
+    pub(crate) rpc_url: Option<String>,
+}
+
+/// Where to find a private key in hex encoding `0x...`
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum SignerSource {
+    /// The key itself
+    Key(String),
+    /// The name of an environment variable holding the key
+    Env(String),
+    /// A file holding the key
+    File(PathBuf),
+}
+
+impl SignerSource {
+    pub(crate) fn resolve(&self) -> Result<String> {
+        match self {
+            SignerSource::Key(key) => Ok(key.clone()),
+            SignerSource::Env(var) => {
+                env::var(var).map_err(|_| eyre!("the signer variable `{var}` is not set"))
+            }
+            SignerSource::File(path) => fs::read_to_string(path)
+                .map_err(|e| eyre!("unable to read signer `{}`: {e}", path.display())),
+        }
+    }
+}
+
+impl Config {
+    /// Loads the config at `path`, or merges the user config
+    /// (`$XDG_CONFIG_HOME/tx-util/config.toml` or `~/.config/tx-util/config.toml`)
+    /// with a project-local `tx-util.toml`. Local profiles take precedence.
+    pub(crate) fn load(path: Option<&Path>) -> Result<Self> {
+        if let Some(path) = path {
+            return Config::from_file(path);
+        }
+
+        let mut config = Config::default();
+        let user = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .map(|dir| dir.join("tx-util").join("config.toml"));
+        for path in user.into_iter().chain([PathBuf::from(LOCAL_CONFIG)]) {
+            if path.is_file() {
+                config.profiles.extend(Config::from_file(&path)?.profiles);
+            }
+        }
+        Ok(config)
+    }
+
+    fn from_file(path: &Path) -> Result<Self> {
+        let input = fs::read_to_string(path)
+            .map_err(|e| eyre!("unable to read config `{}`: {e}", path.display()))?;
+        Config::parse(&input).map_err(|e| eyre!("invalid config `{}`: {e}", path.display()))
+    }
+
+    fn parse(input: &str) -> Result<Self> {
+        Ok(serde_json::from_value(InputFormat::Toml.parse(input)?)?)
+    }
+
+    pub(crate) fn profile(&self, name: &str) -> Result<&Profile> {
+        self.profiles
+            .get(name)
+            .ok_or_else(|| eyre!("unknown profile `{name}`"))
+    }
+}
+
+impl Profile {
+    /// Fills in fields missing from the transaction input with the profile's
+    /// defaults. Fields present in the input are left untouched.
+    pub(crate) fn merge_under(&self, input: &mut Value) -> Result<()> {
+        let input = input
+            .as_object_mut()
+            .ok_or_else(|| eyre!("the transaction input must be an object"))?;
+        let defaults = [
+            ("chainId", self.chain_id.map(|v| json!(v))),
+            ("maxFeePerGas", self.max_fee_per_gas.map(|v| json!(v))),
+            (
+                "maxPriorityFeePerGas",
+                self.max_priority_fee_per_gas.map(|v| json!(v)),
+            ),
+            ("gasLimit", self.gas_limit.map(|v| json!(v))),
+        ];
+        for (key, value) in defaults {
+            if let Some(value) = value {
+                let _ = input.entry(key).or_insert(value);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::Eip1559;
+
+    static CONFIG: &str = r#"
+        [profiles.devnet]
+        chainId = 1337
+        maxFeePerGas = "0x3b9aca00"
+        gasLimit = 100000
+        signer = { env = "TX_UTIL_TEST_UNSET_KEY" }
+        eip7702Revision = "prague"
+        fork = "cancun"
+
+        [profiles.empty]
+    "#;
+
+    #[test]
+    fn parses_config() {
+        let config = Config::parse(CONFIG).unwrap();
+        let devnet = config.profile("devnet").unwrap();
+        assert_eq!(devnet.chain_id, Some(U64::from(1337)));
+        assert_eq!(devnet.gas_limit, Some(U64::from(100000)));
+        assert_eq!(devnet.max_fee_per_gas, Some(U256::from(0x3b9aca00u64)));
+        assert_eq!(devnet.max_priority_fee_per_gas, None);
+        assert_eq!(devnet.eip7702_revision, Some(Eip7702Revision::Prague));
+        assert_eq!(devnet.fork, Some(Fork::Cancun));
+        assert!(devnet.signer.as_ref().unwrap().resolve().is_err());
+        assert!(config.profile("empty").is_ok());
+        assert!(config.profile("mainnet").is_err());
+
+        assert!(Config::parse("[profiles.typo]\nchainID = 1").is_err());
+        assert!(Config::parse("[profiles.large]\nchainId = \"0x10000000000000000\"").is_err());
+    }
+
+    #[test]
+    fn merges_under_input() {
+        let config = Config::parse(CONFIG).unwrap();
+        let mut input = serde_json::json!({
+            "nonce": 0,
+            "maxPriorityFeePerGas": 1,
+            "gasLimit": 21000,
+            "destination": "0x695461EF560Fa4d3a3e7332c9bfcEC261c11a1B6",
+            "amount": 0,
+            "data": "0x",
+            "accessList": []
+        });
+        config
+            .profile("devnet")
+            .unwrap()
+            .merge_under(&mut input)
+            .unwrap();
+        let tx: Eip1559 = serde_json::from_value(input).unwrap();
+        assert_eq!(tx.chain_id, U64::from(1337));
+        assert_eq!(tx.max_fee_per_gas, U256::from(0x3b9aca00u64));
+        assert_eq!(tx.gas_limit, U64::from(21000));
+    }
+}
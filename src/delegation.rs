@@ -0,0 +1,236 @@
+use crate::transaction::{Eip7702, DELEGATION_PREFIX, SECP256K1N_HALF};
+use alloy_primitives::{Address, Bytes, U256, U64};
+use color_eyre::eyre::{eyre, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// The known state of authorities before the transaction is processed
+#[derive(Clone, Debug, Default)]
+pub(crate) struct State {
+    pub(crate) nonces: HashMap<Address, U64>,
+    pub(crate) codes: HashMap<Address, Bytes>,
+}
+
+/// The predicted outcome of processing one authorization
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Outcome {
+    pub(crate) index: usize,
+    pub(crate) authority: Option<Address>,
+    pub(crate) address: Address,
+    /// The code written to the authority, empty when delegation is cleared
+    pub(crate) code: Bytes,
+    pub(crate) clears_delegation: bool,
+    pub(crate) nonce: Option<U64>,
+    /// The authority's nonce before this authorization, if known
+    pub(crate) current_nonce: Option<U64>,
+    /// The authority's nonce after this authorization, if it is applied
+    pub(crate) next_nonce: Option<U64>,
+    /// Why a node would skip this authorization
+    pub(crate) skipped: Option<String>,
+}
+
+/// Predicts how a node processes the `authorization_list` of `tx`, in order.
+///
+/// Nonces and codes missing from `state` are not checked. The transaction
+/// sender's nonce is bumped before authorizations are processed, so a signed
+/// transaction always provides the nonce of its sender.
+///
+/// Fails if the transaction itself is invalid because its nonce cannot be
+/// bumped ([EIP-2681](https://eips.ethereum.org/EIPS/eip-2681)).
+pub(crate) fn simulate(tx: &Eip7702, mut state: State) -> Result<Vec<Outcome>> {
+    let sender_nonce = tx.nonce.checked_add(U64::from(1)).ok_or(eyre!(
+        "the transaction is invalid, its nonce is 2^64-1 (EIP-2681)"
+    ))?;
+    if let Some(sender) = tx.sender() {
+        let _ = state.nonces.insert(sender, sender_nonce);
+    }
+
+    let mut outcomes = Vec::new();
+    for (index, auth) in tx.authorization_list.iter().enumerate() {
+        let authority = auth.authority();
+        let current_nonce = authority.and_then(|a| state.nonces.get(&a).copied());
+        let mut skipped = match (&auth.signature, authority) {
+            _ if !auth.chain_id.is_zero() && auth.chain_id != U256::from(tx.chain_id) => {
+                Some(format!(
+                    "chain id {} does not match the transaction chain id {}",
+                    auth.chain_id, tx.chain_id
+                ))
+            }
+            _ if auth.nonce == Some(U64::MAX) => Some("nonce is at its maximum".to_string()),
+            (None, _) => Some("unsigned".to_string()),
+            (Some(signature), _) if signature.s > SECP256K1N_HALF => {
+                Some("invalid signature, `s` is in the upper half of the curve order".to_string())
+            }
+            (_, None) => Some("invalid signature".to_string()),
+            (_, Some(authority)) => {
+                match (state.codes.get(&authority), auth.nonce, current_nonce) {
+                    (Some(code), _, _) if !code.is_empty() && !is_delegation(code) => {
+                        Some("the authority has code that is not a delegation".to_string())
+                    }
+                    (_, Some(nonce), Some(current)) if nonce != current => Some(format!(
+                        "nonce {nonce} does not match the authority nonce {current}"
+                    )),
+                    _ => None,
+                }
+            }
+        };
+
+        let next_nonce = match (&skipped, authority) {
+            (None, Some(authority)) => {
+                match current_nonce
+                    .or(auth.nonce)
+                    .map(|n| n.checked_add(U64::from(1)))
+                {
+                    Some(None) => {
+                        skipped = Some("the authority nonce is at its maximum".to_string());
+                        None
+                    }
+                    next => {
+                        let next = next.flatten();
+                        if let Some(next) = next {
+                            let _ = state.nonces.insert(authority, next);
+                        }
+                        let _ = state.codes.insert(authority, auth.delegation_code());
+                        next
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        outcomes.push(Outcome {
+            index,
+            authority,
+            address: auth.address,
+            code: auth.delegation_code(),
+            clears_delegation: auth.address.is_zero(),
+            nonce: auth.nonce,
+            current_nonce,
+            next_nonce,
+            skipped,
+        });
+    }
+    Ok(outcomes)
+}
+
+/// Whether `code` is a delegation designator `0xef0100 || address`
+pub(crate) fn is_delegation(code: &[u8]) -> bool {
+    code.len() == 23 && code.starts_with(&DELEGATION_PREFIX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::{signer_address, Eip7702Revision, SECP256K1N};
+    use alloy_primitives::U256;
+
+    static EIP_7702_UNSIGNED: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/transactions/eip7702_unsigned.json"
+    ));
+
+    fn signer() -> Vec<u8> {
+        hex::decode("34954993d403229ee2e01cf6fa8222224935bc47f9534b0c0ea8054764375501").unwrap()
+    }
+
+    #[test]
+    fn simulates_authorizations() {
+        let mut tx: Eip7702 = serde_json::from_str(EIP_7702_UNSIGNED).unwrap();
+        tx.authorization_list.truncate(1);
+        tx = tx.with_revision(Eip7702Revision::Prague).unwrap();
+        let mut clear = tx.authorization_list[0].clone();
+        clear.address = Address::ZERO;
+        clear.nonce = Some(U64::from(3));
+        let mut wrong_chain = tx.authorization_list[0].clone();
+        wrong_chain.chain_id = U256::from(5);
+        tx.authorization_list[0].nonce = Some(U64::from(2));
+        tx.authorization_list.push(clear);
+        tx.authorization_list.push(wrong_chain);
+        tx.authorization_list = tx
+            .authorization_list
+            .into_iter()
+            .map(|auth| auth.sign(signer()))
+            .collect();
+        let mut unsigned = tx.authorization_list[0].clone();
+        unsigned.signature = None;
+        tx.authorization_list.push(unsigned);
+
+        let authority = signer_address(&signer());
+        let mut state = State::default();
+        let _ = state.nonces.insert(authority, U64::from(2));
+        let outcomes = simulate(&tx, state).unwrap();
+
+        assert_eq!(outcomes[0].authority, Some(authority));
+        assert_eq!(outcomes[0].skipped, None);
+        assert_eq!(outcomes[0].code, tx.authorization_list[0].delegation_code());
+        assert_eq!(outcomes[0].next_nonce, Some(U64::from(3)));
+
+        assert!(outcomes[1].clears_delegation);
+        assert!(outcomes[1].code.is_empty());
+        assert_eq!(outcomes[1].current_nonce, Some(U64::from(3)));
+        assert_eq!(outcomes[1].skipped, None);
+
+        assert!(outcomes[2].skipped.as_ref().unwrap().contains("chain id"));
+        assert_eq!(outcomes[2].next_nonce, None);
+        assert_eq!(outcomes[3].skipped.as_deref(), Some("unsigned"));
+
+        // a stale nonce once the first authorization is applied
+        let mut state = State::default();
+        let _ = state.nonces.insert(authority, U64::from(2));
+        tx.authorization_list[1].nonce = Some(U64::from(2));
+        tx.authorization_list[1] = tx.authorization_list[1].clone().sign(signer());
+        let outcomes = simulate(&tx, state).unwrap();
+        assert!(outcomes[1].skipped.as_ref().unwrap().contains("nonce 2"));
+
+        // an authority with code that is not a delegation
+        let mut state = State::default();
+        let _ = state
+            .codes
+            .insert(authority, Bytes::from_static(&[0x60, 0x00]));
+        let outcomes = simulate(&tx, state).unwrap();
+        assert!(outcomes[0].skipped.as_ref().unwrap().contains("code"));
+    }
+
+    #[test]
+    fn rejects_high_s() {
+        let mut tx: Eip7702 = serde_json::from_str(EIP_7702_UNSIGNED).unwrap();
+        let mut auth = tx.authorization_list[0].clone().sign(signer());
+        let signature = auth.signature.as_mut().unwrap();
+        signature.s = SECP256K1N - signature.s;
+        signature.y_parity = !signature.y_parity;
+        tx.authorization_list = vec![auth];
+
+        let outcomes = simulate(&tx, State::default()).unwrap();
+        assert!(outcomes[0].skipped.as_ref().unwrap().contains("upper half"));
+    }
+
+    #[test]
+    fn rejects_maximum_nonces() {
+        let mut tx: Eip7702 = serde_json::from_str(EIP_7702_UNSIGNED).unwrap();
+        tx.authorization_list.truncate(1);
+        tx = tx.with_revision(Eip7702Revision::Prague).unwrap();
+        tx.authorization_list[0].nonce = Some(U64::MAX - U64::from(1));
+        tx.authorization_list[0] = tx.authorization_list[0].clone().sign(signer());
+
+        // the sender's nonce is not wrapped to 0
+        let max = Eip7702 {
+            nonce: U64::MAX,
+            ..tx.clone()
+        }
+        .sign(signer());
+        let error = simulate(&max, State::default()).unwrap_err();
+        assert!(error.to_string().contains("EIP-2681"));
+
+        // a draft authorization without a nonce takes the authority's
+        let authority = signer_address(&signer());
+        let mut tx = tx.with_revision(Eip7702Revision::Draft).unwrap();
+        tx.authorization_list[0].nonce = None;
+        tx.authorization_list[0] = tx.authorization_list[0].clone().sign(signer());
+        let mut state = State::default();
+        let _ = state.nonces.insert(authority, U64::MAX);
+        let outcomes = simulate(&tx, state).unwrap();
+        assert_eq!(outcomes[0].next_nonce, None);
+        assert!(outcomes[0].skipped.as_ref().unwrap().contains("maximum"));
+    }
+}
@@ -0,0 +1,190 @@
+//! Differential tests against [alloy-consensus](https://github.com/alloy-rs/alloy),
+//! comparing encoded transactions, signing hashes, transaction hashes and
+//! `personal_sign` message hashes.
+//!
+//! alloy limits fees to `u128`, so generated values are clamped to that
+//! range, and it only encodes authorizations that are signed, in the
+//! [`Eip7702Revision::Prague`] format.
+
+use crate::{
+    message::{self, MessageScheme},
+    rlp::Encodable,
+    strategies,
+    transaction::{keccak256, AccessListItem, Authorization, Eip1559, Eip7702, Eip7702Revision},
+};
+use alloy_consensus::{SignableTransaction, TxEip1559, TxEip7702};
+use alloy_eips::{eip2930, eip7702};
+use alloy_primitives::{Address, Bytes, FixedBytes, U256, U64};
+use proptest::{collection::vec, prelude::*};
+use reference_primitives as reference;
+
+fn u256(value: U256) -> reference::U256 {
+    reference::U256::from_be_bytes(value.to_be_bytes::<32>())
+}
+
+fn address(value: Address) -> reference::Address {
+    reference::Address::from(value.0 .0)
+}
+
+fn bytes(value: &Bytes) -> reference::Bytes {
+    reference::Bytes::from(value.to_vec())
+}
+
+fn access_list(value: &[AccessListItem]) -> eip2930::AccessList {
+    eip2930::AccessList(
+        value
+            .iter()
+            .map(|item| eip2930::AccessListItem {
+                address: address(item.address),
+                storage_keys: item
+                    .storage_keys
+                    .iter()
+                    .map(|key| reference::B256::from(key.0))
+                    .collect(),
+            })
+            .collect(),
+    )
+}
+
+fn authorization(value: &Authorization) -> eip7702::SignedAuthorization {
+    let signature = value.signature.clone().expect("a signed authorization");
+    eip7702::SignedAuthorization::new_unchecked(
+        eip7702::Authorization {
+            chain_id: u256(value.chain_id),
+            address: address(value.address),
+            nonce: value.nonce.expect("a prague authorization").to(),
+        },
+        signature.y_parity as u8,
+        u256(signature.r),
+        u256(signature.s),
+    )
+}
+
+fn signature(value: &crate::transaction::Signature) -> reference::Signature {
+    reference::Signature::new(u256(value.r), u256(value.s), value.y_parity)
+}
+
+fn eip1559(tx: &Eip1559) -> TxEip1559 {
+    TxEip1559 {
+        chain_id: tx.chain_id.to(),
+        nonce: tx.nonce.to(),
+        gas_limit: tx.gas_limit.to(),
+        max_fee_per_gas: tx.max_fee_per_gas.to(),
+        max_priority_fee_per_gas: tx.max_priority_fee_per_gas.to(),
+        to: match tx.destination {
+            Some(destination) => reference::TxKind::Call(address(destination)),
+            None => reference::TxKind::Create,
+        },
+        value: u256(tx.amount),
+        access_list: access_list(&tx.access_list),
+        input: bytes(&tx.data),
+    }
+}
+
+fn eip7702(tx: &Eip7702) -> TxEip7702 {
+    TxEip7702 {
+        chain_id: tx.chain_id.to(),
+        nonce: tx.nonce.to(),
+        gas_limit: tx.gas_limit.to(),
+        max_fee_per_gas: tx.max_fee_per_gas.to(),
+        max_priority_fee_per_gas: tx.max_priority_fee_per_gas.to(),
+        to: address(tx.destination),
+        value: u256(tx.amount),
+        access_list: access_list(&tx.access_list),
+        authorization_list: tx.authorization_list.iter().map(authorization).collect(),
+        input: bytes(&tx.data),
+    }
+}
+
+/// Clamps the fees alloy stores in `u128`, keeping the maximum
+fn clamp(fees: [&mut U256; 2]) {
+    for fee in fees {
+        *fee = (*fee).min(U256::from(u128::MAX));
+    }
+}
+
+fn signed_eip1559() -> impl Strategy<Value = Eip1559> {
+    (strategies::eip1559(), strategies::signature()).prop_map(|(mut tx, signature)| {
+        clamp([&mut tx.max_fee_per_gas, &mut tx.max_priority_fee_per_gas]);
+        tx.signature = Some(signature);
+        tx
+    })
+}
+
+fn signed_eip7702() -> impl Strategy<Value = Eip7702> {
+    (
+        strategies::eip7702(),
+        strategies::signature(),
+        strategies::signature(),
+    )
+        .prop_map(|(mut tx, signature, auth_signature)| {
+            for auth in tx.authorization_list.iter_mut() {
+                auth.nonce = auth.nonce.or(Some(U64::ZERO));
+                auth.signature = auth.signature.take().or(Some(auth_signature.clone()));
+            }
+            let mut tx = tx.with_revision(Eip7702Revision::Prague).unwrap();
+            clamp([&mut tx.max_fee_per_gas, &mut tx.max_priority_fee_per_gas]);
+            tx.signature = Some(signature);
+            tx
+        })
+}
+
+/// The hash signed by the sender, `keccak256(tx_type || rlp([..]))` without
+/// the signature
+fn signing_hash(tx_type: u8, unsigned: &impl Encodable) -> FixedBytes<32> {
+    keccak256(&[&[tx_type], unsigned.encoded().as_slice()].concat())
+}
+
+proptest! {
+    #[test]
+    fn eip1559_matches_alloy(tx in signed_eip1559()) {
+        let reference = eip1559(&tx);
+        let signed = reference
+            .clone()
+            .into_signed(signature(tx.signature.as_ref().unwrap()));
+        let mut expected = Vec::new();
+        signed.eip2718_encode(&mut expected);
+
+        let bytes = tx.encode_typed();
+        prop_assert_eq!(&bytes, &expected);
+        prop_assert_eq!(keccak256(&bytes).0, signed.hash().0);
+
+        let unsigned = Eip1559 { signature: None, ..tx };
+        prop_assert_eq!(signing_hash(2, &unsigned).0, reference.signature_hash().0);
+    }
+
+    #[test]
+    fn eip7702_matches_alloy(tx in signed_eip7702()) {
+        let reference = eip7702(&tx);
+        let signed = reference
+            .clone()
+            .into_signed(signature(tx.signature.as_ref().unwrap()));
+        let mut expected = Vec::new();
+        signed.eip2718_encode(&mut expected);
+
+        let bytes = tx.encode_typed();
+        prop_assert_eq!(&bytes, &expected);
+        prop_assert_eq!(keccak256(&bytes).0, signed.hash().0);
+
+        for (auth, reference) in tx.authorization_list.iter().zip(&reference.authorization_list) {
+            let unsigned = Authorization { signature: None, ..auth.clone() };
+            prop_assert_eq!(signing_hash(5, &unsigned).0, reference.signature_hash().0);
+        }
+        let unsigned = Eip7702 { signature: None, ..tx };
+        prop_assert_eq!(signing_hash(4, &unsigned).0, reference.signature_hash().0);
+    }
+
+    #[test]
+    fn signatures_recover_like_alloy(tx in signed_eip1559(), signer in strategies::signer()) {
+        let tx = tx.sign(signer);
+        let signed = eip1559(&tx).into_signed(signature(tx.signature.as_ref().unwrap()));
+        let recovered = signed.recover_signer().ok().map(|address| address.0 .0);
+        prop_assert_eq!(tx.sender().map(|address| address.0 .0), recovered);
+    }
+
+    #[test]
+    fn personal_messages_hash_like_alloy(message in vec(any::<u8>(), 0..200)) {
+        let hash = message::hash(MessageScheme::Personal, &message, None).unwrap();
+        prop_assert_eq!(hash.0, reference::eip191_hash_message(&message).0);
+    }
+}
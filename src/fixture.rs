@@ -0,0 +1,307 @@
+use crate::{
+    fork::Fork,
+    rlp::Decodable,
+    transaction::{keccak256, Eip1559, Eip7702, Eip7702Revision, SECP256K1N_HALF},
+    validate::Violation,
+};
+use alloy_primitives::{Address, Bytes, B256, U64};
+use clap::ValueEnum;
+use color_eyre::eyre::{eyre, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Formats of transaction test fixtures
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum FixtureFormat {
+    /// `TransactionTests` of [ethereum/tests](https://github.com/ethereum/tests)
+    #[default]
+    EthereumTests,
+    /// `transaction_test` fixtures of
+    /// [execution-spec-tests](https://github.com/ethereum/execution-spec-tests)
+    Eest,
+}
+
+/// A transaction test: an encoded transaction and its outcome in each fork
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct Fixture {
+    #[serde(rename = "_info")]
+    pub(crate) info: Info,
+    /// Keyed by fork name, e.g. `Prague`
+    pub(crate) result: BTreeMap<String, ForkResult>,
+    pub(crate) txbytes: Bytes,
+}
+
+/// The `_info` of a [`Fixture`]
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct Info {
+    pub(crate) comment: String,
+    #[serde(rename = "fixture-format", skip_serializing_if = "Option::is_none")]
+    pub(crate) fixture_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) source: Option<String>,
+}
+
+/// The outcome of a transaction in one fork, either a `hash` and `sender` or
+/// an `exception`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ForkResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) hash: Option<B256>,
+    /// The smallest valid `gasLimit`, including the calldata floor from Prague
+    pub(crate) intrinsic_gas: U64,
+    /// The lowercase hex address of the sender
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) sender: Option<String>,
+    /// Every rule the transaction breaks, separated by `|`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) exception: Option<String>,
+}
+
+/// Signs the transaction input where it is unsigned and encodes it, as
+/// `encode-tx --no-validate` does
+pub(crate) fn encode(
+    tx_type: u8,
+    input: Value,
+    signer: Option<&[u8]>,
+    authorizers: &[Vec<u8>],
+    revision: Eip7702Revision,
+) -> Result<Vec<u8>> {
+    let signer = || {
+        signer
+            .map(<[u8]>::to_vec)
+            .ok_or(eyre!("a `--signer` is required to sign this transaction"))
+    };
+    let bytes = match tx_type {
+        0x2 => {
+            let mut tx: Eip1559 = serde_json::from_value(input)?;
+            if tx.signature.is_none() {
+                tx = tx.sign(signer()?);
+            }
+            tx.encode_typed()
+        }
+        0x4 => {
+            let tx: Eip7702 = serde_json::from_value(input)?;
+            let mut tx = tx.authorize(revision, authorizers)?;
+            if tx.signature.is_none() {
+                tx = tx.sign(signer()?);
+            }
+            tx.encode_typed()
+        }
+        _ => Err(eyre!("invalid transaction type `{tx_type}`"))?,
+    };
+    Ok(bytes)
+}
+
+/// Builds the fixture of a signed, encoded transaction with its outcome in
+/// each of `forks`
+pub(crate) fn fixture(
+    bytes: &[u8],
+    forks: &[Fork],
+    format: FixtureFormat,
+    source: Option<String>,
+) -> Result<Fixture> {
+    let (tx_type, payload) = bytes
+        .split_first()
+        .ok_or(eyre!("the transaction is empty"))?;
+    let (sender, signature, forks) = match tx_type {
+        0x2 => {
+            let tx = Eip1559::decode_bytes(payload)?;
+            let forks = forks
+                .iter()
+                .map(|f| (*f, tx.intrinsic_gas(*f).minimum, tx.validate(*f)))
+                .collect::<Vec<_>>();
+            (tx.sender(), tx.signature, forks)
+        }
+        0x4 => {
+            let tx = Eip7702::decode_bytes(payload)?;
+            let forks = forks
+                .iter()
+                .map(|f| (*f, tx.intrinsic_gas(*f).minimum, tx.validate(*f)))
+                .collect::<Vec<_>>();
+            (tx.sender(), tx.signature, forks)
+        }
+        _ => Err(eyre!("invalid transaction type `{tx_type}`"))?,
+    };
+    // recovery accepts a high `s`, nodes do not
+    let high_s = signature.is_some_and(|s| s.s > SECP256K1N_HALF);
+    let sender = sender.filter(|_| !high_s);
+
+    let hash = keccak256(bytes);
+    let result = forks
+        .into_iter()
+        .map(|(fork, intrinsic_gas, violations)| {
+            let mut exceptions = violations
+                .iter()
+                .map(|v| exception(v, format))
+                .collect::<Vec<_>>();
+            if sender.is_none() {
+                exceptions.push(invalid_signature(format));
+            }
+            let result = match exceptions.is_empty() {
+                true => ForkResult {
+                    hash: Some(hash),
+                    intrinsic_gas: U64::from(intrinsic_gas),
+                    sender: sender.as_ref().map(lowercase),
+                    exception: None,
+                },
+                false => ForkResult {
+                    hash: None,
+                    intrinsic_gas: U64::from(intrinsic_gas),
+                    sender: None,
+                    exception: Some(exceptions.join("|")),
+                },
+            };
+            (fork.to_string(), result)
+        })
+        .collect();
+
+    Ok(Fixture {
+        info: Info {
+            comment: "exported by tx-util".to_string(),
+            fixture_format: match format {
+                FixtureFormat::EthereumTests => None,
+                FixtureFormat::Eest => Some("transaction_test".to_string()),
+            },
+            source,
+        },
+        result,
+        txbytes: bytes.to_vec().into(),
+    })
+}
+
+/// The name a format gives to `violation`
+fn exception(violation: &Violation, format: FixtureFormat) -> String {
+    let (ethereum_tests, eest) = match violation {
+        Violation::TypeNotActive { tx_type: 0x4, .. } => {
+            ("TR_TypeNotSupported", "TYPE_4_TX_PRE_FORK")
+        }
+        Violation::TypeNotActive { .. } => ("TR_TypeNotSupported", "TYPE_NOT_SUPPORTED"),
+        Violation::PriorityFeeAboveMaxFee { .. } => {
+            ("TR_TipGtFeeCap", "PRIORITY_GREATER_THAN_MAX_FEE_PER_GAS")
+        }
+        Violation::IntrinsicGasTooLow { .. } => ("TR_IntrinsicGas", "INTRINSIC_GAS_TOO_LOW"),
+        Violation::GasLimitAboveCap { .. } => ("TR_GasLimitReached", "GAS_LIMIT_EXCEEDS_MAXIMUM"),
+        Violation::NonceMax => ("TR_NonceHasMaxValue", "NONCE_IS_MAX"),
+        Violation::EmptyAuthorizationList => (
+            "TR_EmptyAuthorizationList",
+            "TYPE_4_EMPTY_AUTHORIZATION_LIST",
+        ),
+        Violation::InitcodeTooLarge { .. } => {
+            ("TR_InitCodeLimitExceeded", "INITCODE_SIZE_EXCEEDED")
+        }
+    };
+    match format {
+        FixtureFormat::EthereumTests => ethereum_tests.to_string(),
+        FixtureFormat::Eest => format!("TransactionException.{eest}"),
+    }
+}
+
+/// The name a format gives to a signature that does not recover a sender,
+/// including a high `s`
+fn invalid_signature(format: FixtureFormat) -> String {
+    match format {
+        FixtureFormat::EthereumTests => "TR_InvalidSignature".to_string(),
+        FixtureFormat::Eest => "TransactionException.INVALID_SIGNATURE_VRS".to_string(),
+    }
+}
+
+fn lowercase(address: &Address) -> String {
+    format!("0x{}", hex::encode(address))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static EIP_1559_UNSIGNED: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/transactions/eip1559_unsigned.json"
+    ));
+
+    static EIP_1559_SIGNED: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/transactions/eip1559_signed.json"
+    ));
+
+    static EIP_7702_UNSIGNED: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/transactions/eip7702_unsigned.json"
+    ));
+
+    fn signer() -> Vec<u8> {
+        hex::decode("34954993d403229ee2e01cf6fa8222224935bc47f9534b0c0ea8054764375501").unwrap()
+    }
+
+    #[test]
+    fn exports_eip1559() {
+        let input = serde_json::from_str(EIP_1559_UNSIGNED).unwrap();
+        let bytes = encode(2, input, Some(&signer()), &[], Eip7702Revision::Draft).unwrap();
+        let exported = fixture(
+            &bytes,
+            &[Fork::London, Fork::Prague],
+            FixtureFormat::EthereumTests,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(exported.txbytes.as_ref(), bytes.as_slice());
+        let london = &exported.result["London"];
+        assert_eq!(london.hash, Some(keccak256(&bytes)));
+        assert_eq!(london.intrinsic_gas, U64::from(21000 + 4300));
+        assert_eq!(
+            london.sender.as_deref(),
+            Some("0x76da6b3693efd723aa7e36d3ef41ac7663fb1af8")
+        );
+        assert_eq!(london.exception, None);
+        assert_eq!(&exported.result["Prague"], london);
+
+        // the checked in signature does not recover a sender
+        let input = serde_json::from_str(EIP_1559_SIGNED).unwrap();
+        let bytes = encode(2, input, None, &[], Eip7702Revision::Draft).unwrap();
+        let exported = fixture(&bytes, &[Fork::London], FixtureFormat::EthereumTests, None);
+        assert_eq!(
+            exported.unwrap().result["London"].exception.as_deref(),
+            Some("TR_InvalidSignature")
+        );
+    }
+
+    #[test]
+    fn exports_exceptions() {
+        let mut input: Value = serde_json::from_str(EIP_7702_UNSIGNED).unwrap();
+        input["gasLimit"] = Value::from(21000);
+        let bytes = encode(
+            4,
+            input,
+            Some(&signer()),
+            &[signer(), signer()],
+            Eip7702Revision::Prague,
+        );
+        // the second authorization has no nonce
+        assert!(bytes.is_err());
+
+        let mut input: Value = serde_json::from_str(EIP_7702_UNSIGNED).unwrap();
+        input["gasLimit"] = Value::from(21000);
+        let bytes = encode(
+            4,
+            input,
+            Some(&signer()),
+            &[signer(), signer()],
+            Eip7702Revision::Draft,
+        )
+        .unwrap();
+
+        let fixture = fixture(&bytes, &[Fork::Cancun], FixtureFormat::Eest, None).unwrap();
+        let cancun = &fixture.result["Cancun"];
+        assert_eq!(cancun.hash, None);
+        assert_eq!(
+            cancun.exception.as_deref(),
+            Some("TransactionException.TYPE_4_TX_PRE_FORK|TransactionException.INTRINSIC_GAS_TOO_LOW")
+        );
+        assert_eq!(
+            fixture.info.fixture_format.as_deref(),
+            Some("transaction_test")
+        );
+    }
+}
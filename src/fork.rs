@@ -0,0 +1,36 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Ethereum mainnet forks, in activation order
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ValueEnum,
+)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum Fork {
+    /// [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559) transactions
+    London,
+    /// Initcode limits ([EIP-3860](https://eips.ethereum.org/EIPS/eip-3860))
+    Shanghai,
+    /// Blob transactions ([EIP-4844](https://eips.ethereum.org/EIPS/eip-4844))
+    Cancun,
+    /// [EIP-7702](https://eips.ethereum.org/EIPS/eip-7702) transactions and the calldata floor
+    /// ([EIP-7623](https://eips.ethereum.org/EIPS/eip-7623))
+    Prague,
+    /// The transaction gas limit cap ([EIP-7825](https://eips.ethereum.org/EIPS/eip-7825))
+    #[default]
+    Osaka,
+}
+
+impl fmt::Display for Fork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Fork::London => "London",
+            Fork::Shanghai => "Shanghai",
+            Fork::Cancun => "Cancun",
+            Fork::Prague => "Prague",
+            Fork::Osaka => "Osaka",
+        };
+        write!(f, "{name}")
+    }
+}
@@ -0,0 +1,211 @@
+use crate::{
+    fork::Fork,
+    transaction::{AccessListItem, Eip1559, Eip7702},
+};
+use alloy_primitives::{Address, Bytes};
+use color_eyre::eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Gas paid by every transaction
+pub(crate) const TX_BASE_GAS: u64 = 21000;
+/// Gas paid by contract creation transactions
+pub(crate) const TX_CREATE_GAS: u64 = 32000;
+/// Gas per zero byte of calldata
+pub(crate) const TX_DATA_ZERO_GAS: u64 = 4;
+/// Gas per non-zero byte of calldata
+pub(crate) const TX_DATA_NON_ZERO_GAS: u64 = 16;
+/// Gas per calldata token in the floor from Prague, a zero byte is one token
+/// and a non-zero byte four
+pub(crate) const TOTAL_COST_FLOOR_PER_TOKEN: u64 = 10;
+/// Gas per address in the access list
+pub(crate) const ACCESS_LIST_ADDRESS_GAS: u64 = 2400;
+/// Gas per storage key in the access list
+pub(crate) const ACCESS_LIST_STORAGE_KEY_GAS: u64 = 1900;
+/// Gas per 32 byte word of initcode, from Shanghai
+pub(crate) const INITCODE_WORD_GAS: u64 = 2;
+/// Gas per authorization, charged as if the authority were an empty account
+pub(crate) const PER_EMPTY_ACCOUNT_GAS: u64 = 25000;
+/// The largest initcode accepted from Shanghai
+pub(crate) const MAX_INITCODE_SIZE: usize = 2 * 24576;
+
+/// The gas charged before execution starts, by component
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct IntrinsicGas {
+    pub(crate) base: u64,
+    pub(crate) calldata: u64,
+    pub(crate) create: u64,
+    /// [EIP-3860](https://eips.ethereum.org/EIPS/eip-3860) initcode word cost
+    pub(crate) initcode: u64,
+    pub(crate) access_list: u64,
+    /// [EIP-7702](https://eips.ethereum.org/EIPS/eip-7702) per-authorization cost
+    pub(crate) authorizations: u64,
+    /// The sum of the components above
+    pub(crate) intrinsic: u64,
+    /// The [EIP-7623](https://eips.ethereum.org/EIPS/eip-7623) calldata floor,
+    /// from Prague
+    pub(crate) floor: Option<u64>,
+    /// The smallest valid `gasLimit`, the greater of `intrinsic` and `floor`
+    pub(crate) minimum: u64,
+}
+
+/// The fields of a transaction input that intrinsic gas depends on, so that
+/// it can be computed before the rest of the transaction is known
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Input {
+    destination: Option<Address>,
+    #[serde(default)]
+    data: Bytes,
+    #[serde(default)]
+    access_list: Vec<AccessListItem>,
+    #[serde(default)]
+    authorization_list: Vec<Value>,
+}
+
+impl IntrinsicGas {
+    /// Computes the intrinsic gas of a transaction input of `tx_type`, which
+    /// only needs the `destination`, `data`, `accessList` and
+    /// `authorizationList` fields
+    pub(crate) fn from_input(tx_type: u8, fork: Fork, input: Value) -> Result<Self> {
+        let input: Input = serde_json::from_value(input)?;
+        let create = match tx_type {
+            0x2 => input.destination.is_none(),
+            0x4 => false,
+            _ => Err(eyre!("invalid transaction type `{tx_type}`"))?,
+        };
+        Ok(IntrinsicGas::new(
+            fork,
+            &input.data,
+            create,
+            &input.access_list,
+            input.authorization_list.len(),
+        ))
+    }
+
+    pub(crate) fn new(
+        fork: Fork,
+        data: &[u8],
+        create: bool,
+        access_list: &[AccessListItem],
+        authorizations: usize,
+    ) -> Self {
+        let zeros = data.iter().filter(|b| **b == 0).count() as u64;
+        let non_zeros = data.len() as u64 - zeros;
+        let mut gas = IntrinsicGas {
+            base: TX_BASE_GAS,
+            calldata: zeros * TX_DATA_ZERO_GAS + non_zeros * TX_DATA_NON_ZERO_GAS,
+            access_list: access_list
+                .iter()
+                .map(|item| {
+                    ACCESS_LIST_ADDRESS_GAS
+                        + item.storage_keys.len() as u64 * ACCESS_LIST_STORAGE_KEY_GAS
+                })
+                .sum(),
+            authorizations: authorizations as u64 * PER_EMPTY_ACCOUNT_GAS,
+            ..Default::default()
+        };
+        if create {
+            gas.create = TX_CREATE_GAS;
+            if fork >= Fork::Shanghai {
+                gas.initcode = data.len().div_ceil(32) as u64 * INITCODE_WORD_GAS;
+            }
+        }
+        gas.intrinsic = gas.base
+            + gas.calldata
+            + gas.create
+            + gas.initcode
+            + gas.access_list
+            + gas.authorizations;
+        if fork >= Fork::Prague {
+            let tokens = zeros + non_zeros * 4;
+            gas.floor = Some(TX_BASE_GAS + tokens * TOTAL_COST_FLOOR_PER_TOKEN);
+        }
+        gas.minimum = gas.intrinsic.max(gas.floor.unwrap_or_default());
+        gas
+    }
+}
+
+impl Eip1559 {
+    pub(crate) fn intrinsic_gas(&self, fork: Fork) -> IntrinsicGas {
+        IntrinsicGas::new(
+            fork,
+            &self.data,
+            self.destination.is_none(),
+            &self.access_list,
+            0,
+        )
+    }
+}
+
+impl Eip7702 {
+    pub(crate) fn intrinsic_gas(&self, fork: Fork) -> IntrinsicGas {
+        IntrinsicGas::new(
+            fork,
+            &self.data,
+            false,
+            &self.access_list,
+            self.authorization_list.len(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static EIP_1559_SIGNED: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/transactions/eip1559_signed.json"
+    ));
+
+    static EIP_7702_UNSIGNED: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/transactions/eip7702_unsigned.json"
+    ));
+
+    #[test]
+    fn computes_intrinsic_gas() {
+        let tx: Eip1559 = serde_json::from_str(EIP_1559_SIGNED).unwrap();
+        let gas = tx.intrinsic_gas(Fork::Prague);
+        // 29 non-zero and 39 zero bytes of `transfer(address,uint256)`
+        assert_eq!(gas.calldata, 29 * 16 + 39 * 4);
+        assert_eq!(gas.access_list, 2400 + 1900);
+        assert_eq!(gas.intrinsic, 21000 + 620 + 4300);
+        assert_eq!(gas.floor, Some(21000 + (39 + 29 * 4) * 10));
+        assert_eq!(gas.minimum, 25920);
+        assert_eq!(tx.intrinsic_gas(Fork::Cancun).floor, None);
+
+        let tx: Eip7702 = serde_json::from_str(EIP_7702_UNSIGNED).unwrap();
+        let gas = tx.intrinsic_gas(Fork::Prague);
+        assert_eq!(gas.authorizations, 2 * 25000);
+        assert_eq!(gas.minimum, 21000 + 4300 + 50000);
+    }
+
+    #[test]
+    fn computes_from_partial_input() {
+        let input = serde_json::json!({ "data": "0x0001", "authorizationList": [{}, {}] });
+        let gas = IntrinsicGas::from_input(4, Fork::Prague, input.clone()).unwrap();
+        assert_eq!(gas.minimum, 21000 + 4 + 16 + 50000);
+        let gas = IntrinsicGas::from_input(2, Fork::Prague, input).unwrap();
+        assert_eq!(gas.create, TX_CREATE_GAS);
+    }
+
+    #[test]
+    fn computes_floor_and_initcode() {
+        // calldata heavy transactions pay the floor
+        let data = vec![0xff; 1000];
+        let gas = IntrinsicGas::new(Fork::Prague, &data, false, &[], 0);
+        assert_eq!(gas.intrinsic, 21000 + 16000);
+        assert_eq!(gas.minimum, 21000 + 40000);
+
+        let gas = IntrinsicGas::new(Fork::London, &data, true, &[], 0);
+        assert_eq!(gas.initcode, 0);
+        assert_eq!(gas.minimum, 21000 + 16000 + 32000);
+
+        let gas = IntrinsicGas::new(Fork::Shanghai, &data, true, &[], 0);
+        assert_eq!(gas.initcode, 32 * 2);
+        assert_eq!(gas.minimum, 21000 + 16000 + 32000 + 64);
+    }
+}
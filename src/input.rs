@@ -0,0 +1,98 @@
+use clap::ValueEnum;
+use color_eyre::eyre::{eyre, Result};
+use serde_json::Value;
+use std::{fs, io, path::Path};
+
+/// Formats accepted for transaction input
+///
+/// Every format is parsed into a json [`Value`] first so that all of them
+/// go through the same deserialization path. This also normalizes integer
+/// types, e.g. TOML only has signed integers which the `U256` deserializer
+/// does not accept.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum InputFormat {
+    Json,
+    /// Json with comments, trailing commas and unquoted keys
+    Json5,
+    Yaml,
+    Toml,
+}
+
+impl InputFormat {
+    /// Infers the format from a file extension
+    pub(crate) fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "json" => Some(InputFormat::Json),
+            "json5" | "jsonc" => Some(InputFormat::Json5),
+            "yaml" | "yml" => Some(InputFormat::Yaml),
+            "toml" => Some(InputFormat::Toml),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn parse(self, input: &str) -> Result<Value> {
+        let value = match self {
+            InputFormat::Json => serde_json::from_str(input.trim())?,
+            InputFormat::Json5 => json5::from_str(input)?,
+            InputFormat::Yaml => serde_yaml::from_str(input)?,
+            InputFormat::Toml => toml::from_str(input)?,
+        };
+        Ok(value)
+    }
+}
+
+/// Reads transaction input from `file`, or from stdin if no file is given.
+///
+/// An explicit `format` always wins. Otherwise the format is inferred from
+/// the file extension, falling back to json.
+pub(crate) fn read_input(file: Option<&Path>, format: Option<InputFormat>) -> Result<Value> {
+    let (input, format) = match file {
+        Some(path) => {
+            let input = fs::read_to_string(path)
+                .map_err(|e| eyre!("unable to read `{}`: {e}", path.display()))?;
+            let format = format.or_else(|| InputFormat::from_path(path));
+            (input, format)
+        }
+        None => (io::read_to_string(io::stdin())?, format),
+    };
+    format.unwrap_or(InputFormat::Json).parse(&input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn infers_format() {
+        assert_eq!(
+            InputFormat::from_path(Path::new("tx.json")),
+            Some(InputFormat::Json)
+        );
+        assert_eq!(
+            InputFormat::from_path(Path::new("tx.jsonc")),
+            Some(InputFormat::Json5)
+        );
+        assert_eq!(
+            InputFormat::from_path(Path::new("tx.YML")),
+            Some(InputFormat::Yaml)
+        );
+        assert_eq!(
+            InputFormat::from_path(Path::new("tx.toml")),
+            Some(InputFormat::Toml)
+        );
+        assert_eq!(InputFormat::from_path(Path::new("tx")), None);
+    }
+
+    #[test]
+    fn normalizes_integers() {
+        let json = InputFormat::Json.parse(r#"{ "nonce": 1 }"#).unwrap();
+        let json5 = InputFormat::Json5.parse("{ nonce: 1, // c\n }").unwrap();
+        let yaml = InputFormat::Yaml.parse("nonce: 1 # c").unwrap();
+        let toml = InputFormat::Toml.parse("nonce = 1 # c").unwrap();
+        assert_eq!(json, json5);
+        assert_eq!(json, yaml);
+        assert_eq!(json, toml);
+        assert!(toml["nonce"].is_u64());
+    }
+}
@@ -0,0 +1,212 @@
+use crate::{abi::Abi, rlp::RlpView};
+use color_eyre::eyre::Result;
+use std::fmt::Write;
+
+/// The field names of a known structure
+#[derive(Clone, Copy)]
+enum Shape {
+    Item,
+    /// A list of named fields, the last three being an optional signature
+    Fields(&'static [(&'static str, Shape)]),
+    /// A list of items of the same shape
+    Each(&'static Shape),
+}
+
+const ACCESS_LIST: Shape = Shape::Each(&Shape::Fields(&[
+    ("address", Shape::Item),
+    ("storageKeys", Shape::Each(&Shape::Item)),
+]));
+
+const AUTHORIZATION_LIST: Shape = Shape::Each(&Shape::Fields(&[
+    ("chainId", Shape::Item),
+    ("address", Shape::Item),
+    ("nonce", Shape::Item),
+    ("yParity", Shape::Item),
+    ("r", Shape::Item),
+    ("s", Shape::Item),
+]));
+
+const EIP1559: Shape = Shape::Fields(&[
+    ("chainId", Shape::Item),
+    ("nonce", Shape::Item),
+    ("maxPriorityFeePerGas", Shape::Item),
+    ("maxFeePerGas", Shape::Item),
+    ("gasLimit", Shape::Item),
+    ("destination", Shape::Item),
+    ("amount", Shape::Item),
+    ("data", Shape::Item),
+    ("accessList", ACCESS_LIST),
+    ("yParity", Shape::Item),
+    ("r", Shape::Item),
+    ("s", Shape::Item),
+]);
+
+const EIP7702: Shape = Shape::Fields(&[
+    ("chainId", Shape::Item),
+    ("nonce", Shape::Item),
+    ("maxPriorityFeePerGas", Shape::Item),
+    ("maxFeePerGas", Shape::Item),
+    ("gasLimit", Shape::Item),
+    ("destination", Shape::Item),
+    ("amount", Shape::Item),
+    ("data", Shape::Item),
+    ("accessList", ACCESS_LIST),
+    ("authorizationList", AUTHORIZATION_LIST),
+    ("yParity", Shape::Item),
+    ("r", Shape::Item),
+    ("s", Shape::Item),
+]);
+
+/// Prints the rlp structure of `bytes` as a tree, one item per line with its
+/// offset, header, payload length and payload in hex.
+///
+/// A leading type byte `0x02` or `0x04` followed by a list is read as a typed
+/// transaction, whose fields are named when their number matches the type.
+/// With `abi`, a non-empty `data` field is followed by its decoded call.
+pub(crate) fn inspect(bytes: &[u8], abi: Option<&Abi>) -> Result<String> {
+    let mut out = format!("{:>6}  {:<10}{:>6}  item\n", "offset", "header", "length");
+    let (start, shape) = match bytes {
+        [tx_type @ (0x2 | 0x4), 0xC0..=0xFF, ..] => {
+            writeln!(out, "{:>6}  {:<10}{:>6}  type {tx_type}", 0, "", "")?;
+            (1, Some(if *tx_type == 0x2 { EIP1559 } else { EIP7702 }))
+        }
+        _ => (0, None),
+    };
+    let view = RlpView::parse(&bytes[start..], start)?;
+    write_view(&mut out, view, None, shape, abi, 0)?;
+    let end = start + view.len();
+    if end < bytes.len() {
+        writeln!(
+            out,
+            "{end:>6}  {:<10}{:>6}  trailing bytes 0x{}",
+            "",
+            bytes.len() - end,
+            hex::encode(&bytes[end..])
+        )?;
+    }
+    Ok(out)
+}
+
+fn write_view(
+    out: &mut String,
+    view: RlpView<'_>,
+    name: Option<String>,
+    shape: Option<Shape>,
+    abi: Option<&Abi>,
+    depth: usize,
+) -> Result<()> {
+    let items = view.items().collect::<Result<Vec<_>>>()?;
+    let label = name.map(|name| format!("{name}: ")).unwrap_or_default();
+    let value = match view.is_list {
+        true => format!("list of {}", items.len()),
+        false => format!("0x{}", hex::encode(view.payload)),
+    };
+    let note = if view.is_canonical() {
+        ""
+    } else {
+        " (non-canonical)"
+    };
+    writeln!(
+        out,
+        "{:>6}  {:<10}{:>6}  {:indent$}{label}{value}{note}",
+        view.offset,
+        hex::encode(view.header),
+        view.payload.len(),
+        "",
+        indent = depth * 2
+    )?;
+    if let (Some(abi), Some("data"), false) = (abi, label.strip_suffix(": "), view.is_list) {
+        if !view.payload.is_empty() {
+            writeln!(
+                out,
+                "{:>6}  {:<10}{:>6}  {:indent$}decodedData: {}",
+                "",
+                "",
+                "",
+                "",
+                serde_json::to_string(&abi.decode_call(view.payload))?,
+                indent = depth * 2
+            )?;
+        }
+    }
+
+    for (i, item) in items.iter().enumerate() {
+        let (name, shape) = match shape {
+            Some(Shape::Fields(fields))
+                if items.len() == fields.len() || items.len() + 3 == fields.len() =>
+            {
+                let (name, shape) = fields[i];
+                (Some(name.to_string()), Some(shape))
+            }
+            Some(Shape::Each(shape)) => (Some(format!("[{i}]")), Some(*shape)),
+            _ => (None, None),
+        };
+        write_view(out, *item, name, shape, abi, depth + 1)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::Eip7702;
+
+    static EIP_7702_SIGNED: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/transactions/eip7702_signed.json"
+    ));
+
+    #[test]
+    fn inspects_raw_rlp() {
+        // `[0x0a, [], 0x00]` with a trailing byte
+        let out = inspect(&hex::decode("c30ac00001").unwrap(), None).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(lines[1], "     0  c3             3  list of 3");
+        assert_eq!(lines[2], "     1                 1    0x0a");
+        assert_eq!(lines[3], "     2  c0             0    list of 0");
+        assert_eq!(lines[4], "     3                 1    0x00");
+        assert_eq!(lines[5], "     4                 1  trailing bytes 0x01");
+    }
+
+    #[test]
+    fn names_transaction_fields() {
+        let tx: Eip7702 = serde_json::from_str(EIP_7702_SIGNED).unwrap();
+        let out = inspect(&tx.encode_typed(), None).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[1], "     0                    type 4");
+        assert!(lines[2].ends_with("  list of 13"));
+        assert!(lines[3].ends_with("  chainId: 0x01"));
+        assert!(lines[4].ends_with("  nonce: 0x"));
+        assert!(out.contains("    accessList: list of 1\n"));
+        assert!(out.contains("        address: 0x8dfdf61f2eb938b207c228b01a2918b196992abf\n"));
+        assert!(out.contains("      [0]: list of 6\n"));
+        assert!(out.contains("        nonce: list of 0\n"));
+        assert!(lines
+            .last()
+            .unwrap()
+            .ends_with("  s: 0x5a0f69dc8009a1e449bfbc8b13220bc40337da1325c261afdac1803f26d0e9d5"));
+    }
+
+    #[test]
+    fn decodes_transaction_data() {
+        let mut tx: Eip7702 = serde_json::from_str(EIP_7702_SIGNED).unwrap();
+        tx.data = hex::decode(concat!(
+            "a9059cbb",
+            "0000000000000000000000005a96834046c1dff63119eb0eed6330fc5007a1d7",
+            "00000000000000000000000000000000000000000000000000000001a1432720",
+        ))
+        .unwrap()
+        .into();
+        let abi = Abi::from_signatures(["transfer(address,uint256)"]).unwrap();
+        let out = inspect(&tx.encode_typed(), Some(&abi)).unwrap();
+        assert!(out.contains(
+            r#"    decodedData: {"args":["0x5a96834046c1DFf63119eB0eed6330fc5007a1d7",7000500000],"function":"transfer","signature":"transfer(address,uint256)"}"#
+        ));
+
+        // an empty `data` is not decoded
+        tx.data = Vec::new().into();
+        let out = inspect(&tx.encode_typed(), Some(&abi)).unwrap();
+        assert!(!out.contains("decodedData"));
+    }
+}
@@ -12,17 +12,63 @@
 )]
 #![deny(rust_2018_idioms, unsafe_code)]
 
+mod abi;
+mod config;
+mod delegation;
+#[cfg(test)]
+mod differential;
+mod fixture;
+mod fork;
+mod gas;
+mod input;
+mod inspect;
+mod message;
+mod mutate;
+mod overrides;
+mod quantity;
 mod rlp;
+mod rpc;
+#[cfg(test)]
+mod strategies;
 mod transaction;
+mod validate;
+mod verify;
 
-use crate::rlp::RlpItem;
-use clap::{CommandFactory, Parser, Subcommand};
+use crate::rlp::{Decodable, RlpItem, RlpReader};
+use abi::{Abi, Call};
+use alloy_primitives::{Address, Bytes, U64};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use color_eyre::eyre::{eyre, Result};
-use std::{io, iter::zip};
-use transaction::{Eip1559, Eip7702};
+use config::{Config, Profile, SignerSource};
+use fixture::FixtureFormat;
+use fork::Fork;
+use input::InputFormat;
+use k256::ecdsa::SigningKey;
+use message::MessageScheme;
+use overrides::Override;
+use rpc::Client;
+use serde_json::Value;
+use std::{
+    collections::hash_map::Entry,
+    fs::{self, File},
+    io::{self, BufReader, Read},
+    path::PathBuf,
+    time::Duration,
+};
+use transaction::{
+    keccak256, signer_address, Authorization, Eip1559, Eip7702, Eip7702Revision, Signature,
+};
+use validate::Violation;
+use verify::Status;
 
 #[cfg(test)]
 use assert_cmd as _;
+#[cfg(test)]
+use criterion as _;
+#[cfg(test)]
+use predicates as _;
+#[cfg(test)]
+use tiny_http as _;
 
 /// WARNING !!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!
 /// Do not use this for generating transactions for the Ethereum mainnet. This tool is
@@ -37,9 +83,11 @@ struct Args {
 
 #[derive(Subcommand, Debug)]
 enum Commands {
-    /// Encodes an EIP-2718 transaction into an rlp-encoded hex value from stdin.
+    /// Encodes an EIP-2718 transaction into an rlp-encoded hex value from stdin
+    /// or `--file`.
     ///
     /// Accepts json input with a `type` field followed by valid tranaction fields.
+    /// Json5, yaml and toml are also accepted, see `--input-format`.
     ///
     /// This currently accepts types `2` and `4` only.
     ///
@@ -72,6 +120,448 @@ enum Commands {
         /// the number of items in the `authorization_list`.
         #[arg(long = "authorizer")]
         authorizers: Vec<String>,
+
+        /// Read the transaction from a file instead of stdin.
+        #[arg(long, short = 'f')]
+        file: Option<PathBuf>,
+
+        /// The format of the transaction input. If omitted, this is inferred
+        /// from the `--file` extension and otherwise defaults to `json`.
+        #[arg(long, value_enum)]
+        input_format: Option<InputFormat>,
+
+        /// Override a transaction field before signing, e.g. `--set nonce=5`
+        /// or `--set authorizationList[1].chainId=0`. May be repeated and is
+        /// applied in order.
+        ///
+        /// Paths use the json field names. Values are parsed as json where
+        /// possible and as strings otherwise.
+        #[arg(long = "set", value_name = "PATH=VALUE")]
+        overrides: Vec<Override>,
+
+        /// A named profile from the config file. Its fields are used as
+        /// defaults for any fields missing from the transaction input.
+        #[arg(long)]
+        profile: Option<String>,
+
+        /// The config file to read profiles from. Defaults to a project-local
+        /// `tx-util.toml` merged over `~/.config/tx-util/config.toml`.
+        #[arg(long, requires = "profile")]
+        config: Option<PathBuf>,
+
+        /// For type 4 transactions only.
+        ///
+        /// The EIP-7702 revision used to encode authorizations. Defaults to
+        /// the profile's revision, or `draft`.
+        #[arg(long, value_enum)]
+        eip7702_revision: Option<Eip7702Revision>,
+
+        /// A JSON-RPC endpoint, e.g. `http://127.0.0.1:8545`. Defaults to the
+        /// profile's `rpcUrl`.
+        #[arg(long)]
+        rpc_url: Option<String>,
+
+        /// Fill missing fields from the `--rpc-url` node before signing.
+        ///
+        /// `chainId`, `nonce`, `maxPriorityFeePerGas`, `maxFeePerGas` and
+        /// `gasLimit` may be left out of the transaction input, as well as the
+        /// `nonce` of unsigned authorizations.
+        #[arg(long)]
+        fill: bool,
+
+        /// Send the encoded transaction to the `--rpc-url` node with
+        /// `eth_sendRawTransaction`. The returned hash is checked against the
+        /// locally computed hash.
+        #[arg(long)]
+        send: bool,
+
+        /// Wait for the receipt of a sent transaction and report its status,
+        /// gas used and, for type 4 transactions, the code of each authority.
+        #[arg(long, requires = "send")]
+        wait: bool,
+
+        /// How many seconds to `--wait` for a receipt.
+        #[arg(long, default_value_t = 120, requires = "wait")]
+        timeout: u64,
+
+        /// ABI-encode `data` as a call to this function signature, e.g.
+        /// `transfer(address,uint256)`, replacing any `data` in the input.
+        ///
+        /// The transaction input may instead contain a `call` object with a
+        /// `signature` and a list of `args`.
+        #[arg(long)]
+        calldata_sig: Option<String>,
+
+        /// An argument for `--calldata-sig`, repeated in order. Lists and
+        /// tuples are written as `[1,2]` or json.
+        #[arg(long = "arg", requires = "calldata_sig", value_parser = parse_arg)]
+        args: Vec<Value>,
+
+        /// The fork whose consensus rules the transaction is validated
+        /// against. Defaults to the profile's fork, or `osaka`.
+        #[arg(long, value_enum)]
+        fork: Option<Fork>,
+
+        /// Skip validation, e.g. to encode intentionally invalid test vectors.
+        #[arg(long)]
+        no_validate: bool,
+
+        /// Replace the transaction signature with its high-s variant
+        /// `(r, n - s, !y_parity)`, which is rejected since EIP-2.
+        #[arg(long)]
+        high_s: bool,
+
+        /// For type 4 transactions only.
+        ///
+        /// Replace the signature of the authorization at this index with its
+        /// high-s variant before the transaction is signed. May be repeated.
+        #[arg(long, value_name = "INDEX")]
+        high_s_auth: Vec<usize>,
+    },
+
+    /// Decodes an rlp-encoded EIP-2718 transaction from an argument or stdin
+    /// and prints it as json in the `encode-tx` input format.
+    ///
+    /// With `--abi` or `--signature`, the transaction `data` is decoded as a
+    /// function call and shown under `decodedData`.
+    ///
+    /// With `--stream`, a binary file of concatenated transactions is read one
+    /// transaction at a time and each is printed as one line of json.
+    ///
+    /// This currently accepts types `2` and `4` only.
+    #[command(long_about, verbatim_doc_comment)]
+    DecodeTx {
+        /// The transaction in hex encoding `0x...`. Read from stdin if omitted.
+        tx: Option<String>,
+
+        /// Decode a binary file of concatenated typed transactions
+        /// `type || rlp([..])`, e.g. a transaction dump, without reading it
+        /// into memory.
+        #[arg(long, conflicts_with = "tx")]
+        stream: Option<PathBuf>,
+
+        /// A contract ABI to decode `data` against. Either a json ABI, a
+        /// compiler artifact with an `abi` field, or a file with one function
+        /// signature per line. May be repeated.
+        #[arg(long)]
+        abi: Vec<PathBuf>,
+
+        /// A function signature to decode `data` against, e.g.
+        /// `transfer(address,uint256)`. May be repeated.
+        #[arg(long)]
+        signature: Vec<String>,
+    },
+
+    /// Replaces signatures in a signed transaction from stdin or `--file` with
+    /// their high-s variant `(r, n - s, !y_parity)` and prints it as json.
+    ///
+    /// High-s signatures are rejected since EIP-2, for transactions as well as
+    /// authorizations.
+    #[command(long_about, verbatim_doc_comment)]
+    Malleate {
+        /// Transaction type. Types `2` and `4` accepted.
+        #[arg(long, short = 't')]
+        tx_type: u8,
+
+        /// Read the transaction from a file instead of stdin.
+        #[arg(long, short = 'f')]
+        file: Option<PathBuf>,
+
+        /// The format of the transaction input. If omitted, this is inferred
+        /// from the `--file` extension and otherwise defaults to `json`.
+        #[arg(long, value_enum)]
+        input_format: Option<InputFormat>,
+
+        /// Malleate the transaction signature.
+        #[arg(long = "tx", required_unless_present = "auths")]
+        tx_signature: bool,
+
+        /// For type 4 transactions only.
+        ///
+        /// Malleate the signature of the authorization at this index. May be
+        /// repeated.
+        #[arg(long = "auth", value_name = "INDEX")]
+        auths: Vec<usize>,
+    },
+
+    /// Computes the intrinsic gas of a transaction from stdin or `--file`,
+    /// i.e. the smallest `gasLimit` it can be included with.
+    ///
+    /// Only `destination`, `data` (or `call`), `accessList` and
+    /// `authorizationList` are read, other fields may be left out. A type 2
+    /// transaction without a `destination` is a contract creation.
+    ///
+    /// Prints the cost of each component, the EIP-7623 calldata floor from
+    /// Prague and the resulting `minimum` as json.
+    #[command(long_about, verbatim_doc_comment)]
+    IntrinsicGas {
+        /// Transaction type. Types `2` and `4` accepted.
+        #[arg(long, short = 't')]
+        tx_type: u8,
+
+        /// Read the transaction from a file instead of stdin.
+        #[arg(long, short = 'f')]
+        file: Option<PathBuf>,
+
+        /// The format of the transaction input. If omitted, this is inferred
+        /// from the `--file` extension and otherwise defaults to `json`.
+        #[arg(long, value_enum)]
+        input_format: Option<InputFormat>,
+
+        /// The fork whose gas schedule is used. Defaults to `osaka`.
+        #[arg(long, value_enum, default_value_t)]
+        fork: Fork,
+    },
+
+    /// Produces malformed variants of a signed, valid rlp-encoded transaction
+    /// from an argument or stdin, for negative testing.
+    ///
+    /// Each variant breaks one thing, e.g. a non-canonical length, a leading
+    /// zero integer, a wrong type byte, a high-s signature or a wrong chain id
+    /// in an authorization, and is labelled with the reason a node rejects it.
+    ///
+    /// Prints a json list of `name`, `reason` and `tx`.
+    #[command(long_about, verbatim_doc_comment)]
+    Mutate {
+        /// The transaction in hex encoding `0x...`. Read from stdin if omitted.
+        tx: Option<String>,
+
+        /// A private key in hex encoding `0x...` to sign variants with changed
+        /// fields again, so that each differs in one way only. Without it the
+        /// original signature is kept, which recovers to a different sender.
+        #[arg(long)]
+        signer: Option<String>,
+
+        /// The fork whose gas schedule is used for `gas-limit-below-intrinsic`.
+        /// Defaults to `osaka`.
+        #[arg(long, value_enum, default_value_t)]
+        fork: Fork,
+    },
+
+    /// Exports transaction inputs as transaction test fixtures, one test per
+    /// file named after it.
+    ///
+    /// Each input is signed where needed and encoded as `txbytes`. For every
+    /// `--fork`, the `result` holds the `hash`, `sender` and `intrinsicGas` of
+    /// a valid transaction, or the `exception` of an invalid one.
+    ///
+    /// Writes `TransactionTests` of ethereum/tests by default, or
+    /// execution-spec-tests `transaction_test` fixtures with `--format eest`.
+    #[command(long_about, verbatim_doc_comment)]
+    ExportFixtures {
+        /// The transaction inputs.
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+
+        /// Transaction type of inputs without a `type` field. Types `2` and
+        /// `4` accepted.
+        #[arg(long, short = 't')]
+        tx_type: Option<u8>,
+
+        /// The format of the transaction inputs. If omitted, this is inferred
+        /// from each file extension and otherwise defaults to `json`.
+        #[arg(long, value_enum)]
+        input_format: Option<InputFormat>,
+
+        /// A private key in hex encoding `0x...` for unsigned inputs.
+        #[arg(long)]
+        signer: Option<String>,
+
+        /// For type 4 transactions only.
+        ///
+        /// Private keys in hex encoding `0x...` for unsigned authorizations,
+        /// one per item in the `authorization_list`.
+        #[arg(long = "authorizer")]
+        authorizers: Vec<String>,
+
+        /// For type 4 transactions only.
+        ///
+        /// The EIP-7702 revision used to encode authorizations.
+        #[arg(long, value_enum, default_value_t)]
+        eip7702_revision: Eip7702Revision,
+
+        /// A fork to report the result in. May be repeated, defaults to
+        /// every fork.
+        #[arg(long = "fork", value_enum)]
+        forks: Vec<Fork>,
+
+        /// The fixture format.
+        #[arg(long, value_enum, default_value_t)]
+        format: FixtureFormat,
+
+        /// Write the fixtures to a file instead of stdout.
+        #[arg(long, short = 'o')]
+        output: Option<PathBuf>,
+    },
+
+    /// Works with rlp that is not necessarily a transaction.
+    #[command(subcommand)]
+    Rlp(RlpCommands),
+
+    /// Verifies transaction test vectors in the format of ethereum/tests or
+    /// execution-spec-tests, from json files or directories of them.
+    ///
+    /// Each `txbytes` is decoded, its sender recovered and its hash and
+    /// intrinsic gas recomputed, then compared to the expected `result` of
+    /// every fork. A case expecting an `exception` passes if the transaction
+    /// is rejected for any reason.
+    ///
+    /// Prints one line per case and fork and fails if any case fails. Legacy,
+    /// type 1 and type 3 transactions and unknown forks are skipped.
+    #[command(long_about, verbatim_doc_comment)]
+    VerifyVectors {
+        /// Json files or directories, searched recursively.
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+    },
+
+    /// Signs an off-chain message from an argument or stdin and prints the
+    /// hash, the signer and the signature as json.
+    ///
+    /// The message is hashed as in EIP-191 `personal_sign` by default, as
+    /// version `0x00` data with an intended validator with `--scheme
+    /// validator`, or signed as a 32 byte hash with `--scheme prehash`.
+    ///
+    /// The `signature` is `r || s || v` with `v` in `{27, 28}`.
+    #[command(long_about, verbatim_doc_comment)]
+    SignMessage {
+        /// The message, utf-8 text unless `--hex` is set. Read from stdin if
+        /// omitted.
+        message: Option<String>,
+
+        /// A private key in hex encoding `0x...`.
+        #[arg(long)]
+        signer: String,
+
+        /// How the message is hashed before it is signed.
+        #[arg(long, value_enum, default_value_t)]
+        scheme: MessageScheme,
+
+        /// The address of the intended validator, for `--scheme validator`.
+        #[arg(long, required_if_eq("scheme", "validator"))]
+        validator: Option<Address>,
+
+        /// The message is hex encoded `0x...`. Implied by `--scheme prehash`.
+        #[arg(long)]
+        hex: bool,
+    },
+
+    /// Recovers the signer of an off-chain message from an argument or stdin,
+    /// signed as with `sign-message`, and prints it.
+    ///
+    /// With `--address`, fails unless the message was signed by it.
+    #[command(long_about, verbatim_doc_comment)]
+    VerifyMessage {
+        /// The message, utf-8 text unless `--hex` is set. Read from stdin if
+        /// omitted.
+        message: Option<String>,
+
+        /// The signature `r || s || v` in hex encoding `0x...`, with `v` in
+        /// `{0, 1, 27, 28}`.
+        #[arg(long)]
+        signature: String,
+
+        /// The address expected to have signed the message.
+        #[arg(long)]
+        address: Option<Address>,
+
+        /// How the message is hashed before it is signed.
+        #[arg(long, value_enum, default_value_t)]
+        scheme: MessageScheme,
+
+        /// The address of the intended validator, for `--scheme validator`.
+        #[arg(long, required_if_eq("scheme", "validator"))]
+        validator: Option<Address>,
+
+        /// The message is hex encoded `0x...`. Implied by `--scheme prehash`.
+        #[arg(long)]
+        hex: bool,
+    },
+
+    /// Predicts how a node processes the authorizations of an rlp-encoded
+    /// type 4 transaction from an argument or stdin.
+    ///
+    /// Reports, for each authorization in order, the authority, the code
+    /// written to it (`0xef0100 || address`, or none when delegating to the
+    /// zero address clears the delegation), the nonce bump and why it would
+    /// be skipped: a wrong chain id, a nonce mismatch, an invalid signature
+    /// or an authority with other code.
+    ///
+    /// Authority nonces and codes are only checked when known, from `--nonce`
+    /// or an `--rpc-url` node. The transaction sender's nonce is always known.
+    #[command(long_about, verbatim_doc_comment)]
+    SimulateAuth {
+        /// The transaction in hex encoding `0x...`. Read from stdin if omitted.
+        tx: Option<String>,
+
+        /// The current nonce of an authority, e.g. `--nonce 0x76dA...1AF8=3`.
+        /// May be repeated.
+        #[arg(long = "nonce", value_name = "ADDRESS=NONCE", value_parser = parse_nonce)]
+        nonces: Vec<(Address, U64)>,
+
+        /// A JSON-RPC endpoint to read the nonce and code of authorities
+        /// missing from `--nonce`.
+        #[arg(long)]
+        rpc_url: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum RlpCommands {
+    /// Prints the rlp structure of hex from an argument or stdin as a tree.
+    ///
+    /// Each item is shown with its offset, its header (the prefix byte and any
+    /// length bytes), its payload length and its payload in hex. Non-canonical
+    /// headers and trailing bytes are marked.
+    ///
+    /// A leading type byte `0x02` or `0x04` is read as a typed transaction,
+    /// whose fields are named. With `--abi` or `--signature`, its `data` is
+    /// decoded as a function call and shown under `decodedData`.
+    #[command(long_about, verbatim_doc_comment)]
+    Inspect {
+        /// The rlp in hex encoding `0x...`. Read from stdin if omitted.
+        rlp: Option<String>,
+
+        /// A contract ABI to decode `data` against, as for `decode-tx`. May be
+        /// repeated.
+        #[arg(long)]
+        abi: Vec<PathBuf>,
+
+        /// A function signature to decode `data` against, e.g.
+        /// `transfer(address,uint256)`. May be repeated.
+        #[arg(long)]
+        signature: Vec<String>,
+    },
+
+    /// Encodes a json tree from an argument, stdin or `--file` as rlp.
+    ///
+    /// Lists are arrays and data is a hex string `0x...` or a non-negative
+    /// integer, written in big endian without leading zeros.
+    ///
+    /// ```no_run
+    /// ["0x01", 0, [], ["0xdeadbeef", 1024]]
+    /// ```
+    #[command(long_about, verbatim_doc_comment)]
+    Encode {
+        /// The json tree. Read from `--file` or stdin if omitted.
+        json: Option<String>,
+
+        /// Read the tree from a file instead of stdin.
+        #[arg(long, short = 'f', conflicts_with = "json")]
+        file: Option<PathBuf>,
+
+        /// The format of the tree. If omitted, this is inferred from the
+        /// `--file` extension and otherwise defaults to `json`.
+        #[arg(long, value_enum)]
+        input_format: Option<InputFormat>,
+    },
+
+    /// Decodes rlp in hex from an argument or stdin into the json tree read
+    /// by `rlp encode`, with all data as hex strings.
+    #[command(long_about, verbatim_doc_comment)]
+    Decode {
+        /// The rlp in hex encoding `0x...`. Read from stdin if omitted.
+        rlp: Option<String>,
     },
 }
 
@@ -84,59 +574,551 @@ fn main() -> Result<()> {
             tx_type,
             signer,
             authorizers,
-        }) => match tx_type {
-            0x2 => {
-                let stdin = io::read_to_string(io::stdin())?;
-                let tx: Eip1559 = serde_json::from_str(stdin.trim())?;
-                let ast: RlpItem = if tx.signature.is_none() {
-                    let signer =
-                        signer.ok_or(eyre!("a `--signer` is required to sign this transaction"))?;
-                    let signer = hex::decode(signer.trim().trim_start_matches("0x"))?;
-                    if signer.len() != 32 {
-                        Err(eyre!("the supplied `--signer` is invalid"))?;
+            file,
+            input_format,
+            overrides,
+            profile,
+            config,
+            eip7702_revision,
+            rpc_url,
+            fill,
+            send,
+            wait,
+            timeout,
+            calldata_sig,
+            args,
+            fork,
+            no_validate,
+            high_s,
+            high_s_auth,
+        }) => {
+            let profile = match profile {
+                Some(name) => Config::load(config.as_deref())?.profile(&name)?.clone(),
+                None => Profile::default(),
+            };
+            // the profile's signer is only resolved when a key is needed, an
+            // already signed transaction does not need its variable or file
+            let signer = signer.map(SignerSource::Key).or(profile.signer.clone());
+            let resolve_signer = || -> Result<Vec<u8>> {
+                let source = signer
+                    .as_ref()
+                    .ok_or(eyre!("a `--signer` is required to sign this transaction"))?;
+                decode_key(&source.resolve()?, "--signer")
+            };
+            let authorizers = authorizers
+                .iter()
+                .map(|a| decode_key(a, "--authorizer"))
+                .collect::<Result<Vec<_>>>()?;
+            let fork = fork.or(profile.fork).unwrap_or_default();
+            let client = rpc_url
+                .or(profile.rpc_url.clone())
+                .map(|url| Client::new(&url));
+
+            let mut input = input::read_input(file.as_deref(), input_format)?;
+            profile.merge_under(&mut input)?;
+            abi::expand_call(&mut input)?;
+            if let Some(signature) = calldata_sig {
+                let call = Call { signature, args };
+                if let Some(input) = input.as_object_mut() {
+                    let _ = input.insert("data".into(), serde_json::to_value(call.encode()?)?);
+                }
+            }
+            let sender = match (fill, &signer) {
+                (true, Some(_)) => Some(signer_address(&resolve_signer()?)),
+                _ => None,
+            };
+            let estimate_gas = if fill {
+                let client = client
+                    .as_ref()
+                    .ok_or(eyre!("`--fill` requires an `--rpc-url`"))?;
+                let authorities = authorizers
+                    .iter()
+                    .map(|a| signer_address(a))
+                    .collect::<Vec<_>>();
+                rpc::fill(client, &mut input, sender, &authorities)?
+            } else {
+                false
+            };
+
+            let (bytes, authorizations) = match tx_type {
+                0x2 => {
+                    if !high_s_auth.is_empty() {
+                        Err(eyre!("type 2 transactions have no authorizations"))?;
                     }
-                    tx.sign(signer).into()
-                } else {
-                    tx.into()
-                };
-                let mut bytes: Vec<u8> = ast.into();
-                bytes.insert(0, 2);
-                print!("0x{}", hex::encode(bytes));
+                    let tx: Eip1559 = serde_json::from_value(input)?;
+                    let mut tx = overrides::apply(tx, &overrides)?;
+                    if let (true, Some(client)) = (estimate_gas, &client) {
+                        tx.gas_limit = client.estimate_gas(tx_type, sender, &tx)?;
+                    }
+                    if !no_validate {
+                        check(tx.validate(fork), fork)?;
+                    }
+                    if tx.signature.is_none() {
+                        tx = tx.sign(resolve_signer()?);
+                    }
+                    if high_s {
+                        tx.signature = tx.signature.map(Signature::malleate);
+                    }
+                    let bytes = tx.encode_typed();
+                    (bytes, Vec::new())
+                }
+                0x4 => {
+                    let mut tx: Eip7702 = serde_json::from_value(input)?;
+                    tx = overrides::apply(tx, &overrides)?;
+                    let revision = eip7702_revision
+                        .or(profile.eip7702_revision)
+                        .unwrap_or_default();
+                    tx = tx.authorize(revision, &authorizers)?;
+                    malleate_authorizations(&mut tx, &high_s_auth)?;
+                    if let (true, Some(client)) = (estimate_gas, &client) {
+                        tx.gas_limit = client.estimate_gas(tx_type, sender, &tx)?;
+                    }
+                    if !no_validate {
+                        check(tx.validate(fork), fork)?;
+                    }
+                    let authorizations = tx.authorization_list.clone();
+                    if tx.signature.is_none() {
+                        tx = tx.sign(resolve_signer()?);
+                    }
+                    if high_s {
+                        tx.signature = tx.signature.map(Signature::malleate);
+                    }
+                    let bytes = tx.encode_typed();
+                    (bytes, authorizations)
+                }
+                _ => Err(eyre!("invalid transaction type `{tx_type}`"))?,
+            };
+            print!("0x{}", hex::encode(&bytes));
+
+            if send {
+                let client = client
+                    .as_ref()
+                    .ok_or(eyre!("`--send` requires an `--rpc-url`"))?;
+                let timeout = wait.then_some(Duration::from_secs(timeout));
+                broadcast(client, &bytes, &authorizations, timeout)?;
             }
-            0x4 => {
-                let stdin = io::read_to_string(io::stdin())?;
-                let mut tx: Eip7702 = serde_json::from_str(stdin.trim())?;
-                if tx.authorization_list.iter().any(|a| a.signature.is_none()) {
-                    if tx.authorization_list.len() != authorizers.len() {
-                        Err(eyre!("the number of `--authorizer` must be equal to the number of items in the `authorization_list`"))?;
+        }
+        Some(Commands::DecodeTx {
+            tx,
+            stream,
+            abi,
+            signature,
+        }) => {
+            let functions = load_abi(abi, signature)?;
+            let decode = |bytes: &[u8]| -> Result<Value> {
+                let mut json = decode_tx(bytes)?;
+                if let Some(functions) = &functions {
+                    let data: Bytes = serde_json::from_value(json["data"].clone())?;
+                    if !data.is_empty() {
+                        json["decodedData"] = functions.decode_call(&data);
                     }
-                    let mut signers = Vec::new();
-                    for a in authorizers {
-                        let signer = hex::decode(a.trim().trim_start_matches("0x"))?;
-                        if signer.len() != 32 {
-                            Err(eyre!("a supplied `--authorizer` is invalid"))?;
-                        }
-                        signers.push(signer);
+                }
+                Ok(json)
+            };
+
+            match stream {
+                Some(path) => {
+                    let file = File::open(&path)
+                        .map_err(|e| eyre!("unable to read `{}`: {e}", path.display()))?;
+                    let mut items = RlpReader::new(BufReader::new(file));
+                    loop {
+                        let offset = items.offset;
+                        let Some(tx_type) = items.next().transpose()? else {
+                            break;
+                        };
+                        let [tx_type @ 0x00..=0x7F] = tx_type[..] else {
+                            Err(eyre!(
+                                "expected a transaction type at offset {offset}, legacy transactions are not supported"
+                            ))?
+                        };
+                        let payload = items
+                            .next()
+                            .transpose()?
+                            .ok_or(eyre!("the transaction at offset {offset} is truncated"))?;
+                        let json = decode(&[&[tx_type], payload.as_slice()].concat())
+                            .map_err(|e| eyre!("the transaction at offset {offset}: {e}"))?;
+                        println!("{}", serde_json::to_string(&json)?);
                     }
-                    tx.authorization_list = zip(tx.authorization_list, signers)
-                        .map(|(auth, signer)| auth.sign(signer))
-                        .collect::<Vec<_>>();
                 }
-                let ast: RlpItem = if tx.signature.is_none() {
-                    let signer =
-                        signer.ok_or(eyre!("a `--signer` is required to sign this transaction"))?;
-                    let signer = hex::decode(signer.trim().trim_start_matches("0x"))?;
-                    tx.sign(signer).into()
-                } else {
-                    tx.into()
+                None => {
+                    let json = decode(&read_tx(tx)?)?;
+                    println!("{}", serde_json::to_string_pretty(&json)?);
+                }
+            }
+        }
+        Some(Commands::Malleate {
+            tx_type,
+            file,
+            input_format,
+            tx_signature,
+            auths,
+        }) => {
+            let input = input::read_input(file.as_deref(), input_format)?;
+            let mut json = match tx_type {
+                0x2 => {
+                    if !auths.is_empty() {
+                        Err(eyre!("type 2 transactions have no authorizations"))?;
+                    }
+                    let mut tx: Eip1559 = serde_json::from_value(input)?;
+                    tx.signature = Some(
+                        tx.signature
+                            .ok_or(eyre!("the transaction is not signed"))?
+                            .malleate(),
+                    );
+                    serde_json::to_value(tx)?
+                }
+                0x4 => {
+                    let mut tx: Eip7702 = serde_json::from_value(input)?;
+                    malleate_authorizations(&mut tx, &auths)?;
+                    if tx_signature {
+                        tx.signature = Some(
+                            tx.signature
+                                .ok_or(eyre!("the transaction is not signed"))?
+                                .malleate(),
+                        );
+                    }
+                    serde_json::to_value(tx)?
+                }
+                _ => Err(eyre!("invalid transaction type `{tx_type}`"))?,
+            };
+            if let Some(json) = json.as_object_mut() {
+                let _ = json.insert("type".into(), Value::from(tx_type));
+            }
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+        Some(Commands::IntrinsicGas {
+            tx_type,
+            file,
+            input_format,
+            fork,
+        }) => {
+            let mut input = input::read_input(file.as_deref(), input_format)?;
+            abi::expand_call(&mut input)?;
+            let gas = gas::IntrinsicGas::from_input(tx_type, fork, input)?;
+            println!("{}", serde_json::to_string_pretty(&gas)?);
+        }
+        Some(Commands::Mutate { tx, signer, fork }) => {
+            let signer = signer
+                .map(|signer| decode_key(&signer, "--signer"))
+                .transpose()?;
+            let mutants = mutate::mutate(&read_tx(tx)?, signer.as_deref(), fork)?;
+            println!("{}", serde_json::to_string_pretty(&mutants)?);
+        }
+        Some(Commands::ExportFixtures {
+            files,
+            tx_type,
+            input_format,
+            signer,
+            authorizers,
+            eip7702_revision,
+            mut forks,
+            format,
+            output,
+        }) => {
+            let signer = signer
+                .map(|signer| decode_key(&signer, "--signer"))
+                .transpose()?;
+            let authorizers = authorizers
+                .iter()
+                .map(|a| decode_key(a, "--authorizer"))
+                .collect::<Result<Vec<_>>>()?;
+            if forks.is_empty() {
+                forks = Fork::value_variants().to_vec();
+            }
+
+            let mut fixtures = serde_json::Map::new();
+            for file in files {
+                let input = input::read_input(Some(&file), input_format)?;
+                let tx_type = match input.get("type") {
+                    Some(tx_type) => serde_json::from_value(tx_type.clone())?,
+                    None => tx_type
+                        .ok_or(eyre!("`{}` has no `type`, use `--tx-type`", file.display()))?,
                 };
-                let mut bytes: Vec<u8> = ast.into();
-                bytes.insert(0, 4);
-                print!("0x{}", hex::encode(bytes));
+                let bytes = fixture::encode(
+                    tx_type,
+                    input,
+                    signer.as_deref(),
+                    &authorizers,
+                    eip7702_revision,
+                )?;
+                let source = file.display().to_string();
+                let fixture = fixture::fixture(&bytes, &forks, format, Some(source))?;
+                let name = file
+                    .file_stem()
+                    .ok_or(eyre!("`{}` is not a file", file.display()))?
+                    .to_string_lossy()
+                    .to_string();
+                if fixtures.contains_key(&name) {
+                    Err(eyre!("more than one input is named `{name}`"))?;
+                }
+                let _ = fixtures.insert(name, serde_json::to_value(fixture)?);
+            }
+
+            let fixtures = serde_json::to_string_pretty(&fixtures)?;
+            match output {
+                Some(path) => fs::write(path, fixtures + "\n")?,
+                None => println!("{fixtures}"),
+            }
+        }
+        Some(Commands::Rlp(RlpCommands::Inspect {
+            rlp,
+            abi,
+            signature,
+        })) => {
+            let functions = load_abi(abi, signature)?;
+            print!("{}", inspect::inspect(&read_tx(rlp)?, functions.as_ref())?);
+        }
+        Some(Commands::Rlp(RlpCommands::Encode {
+            json,
+            file,
+            input_format,
+        })) => {
+            let tree = match json {
+                Some(json) => input_format.unwrap_or(InputFormat::Json).parse(&json)?,
+                None => input::read_input(file.as_deref(), input_format)?,
+            };
+            let bytes: Vec<u8> = serde_json::from_value::<RlpItem>(tree)?.into();
+            print!("0x{}", hex::encode(bytes));
+        }
+        Some(Commands::Rlp(RlpCommands::Decode { rlp })) => {
+            let item = RlpItem::decode(&read_tx(rlp)?)?;
+            println!("{}", serde_json::to_string_pretty(&item)?);
+        }
+        Some(Commands::VerifyVectors { paths }) => {
+            let reports = verify::verify_paths(&paths)?;
+            for report in &reports {
+                println!("{report}");
+            }
+            let count = |status| reports.iter().filter(|r| r.status == status).count();
+            let failed = count(Status::Fail);
+            println!(
+                "\n{} passed, {failed} failed, {} skipped",
+                count(Status::Pass),
+                count(Status::Skip)
+            );
+            if failed > 0 {
+                Err(eyre!("{failed} of {} cases failed", reports.len()))?;
+            }
+        }
+        Some(Commands::SimulateAuth {
+            tx,
+            nonces,
+            rpc_url,
+        }) => {
+            let bytes = read_tx(tx)?;
+            if bytes.first() != Some(&4) {
+                Err(eyre!("only type 4 transactions have authorizations"))?;
             }
-            _ => Err(eyre!("invalid transaction type`"))?,
-        },
+            let tx = Eip7702::decode_bytes(&bytes[1..])?;
+
+            let mut state = delegation::State {
+                nonces: nonces.into_iter().collect(),
+                ..Default::default()
+            };
+            if let Some(client) = rpc_url.map(|url| Client::new(&url)) {
+                for authority in tx.authorization_list.iter().filter_map(|a| a.authority()) {
+                    if let Entry::Vacant(entry) = state.nonces.entry(authority) {
+                        let _ = entry.insert(client.transaction_count(authority)?);
+                    }
+                    if let Entry::Vacant(entry) = state.codes.entry(authority) {
+                        let _ = entry.insert(client.code(authority)?);
+                    }
+                }
+            }
+            let outcomes = delegation::simulate(&tx, state)?;
+            println!("{}", serde_json::to_string_pretty(&outcomes)?);
+        }
+        Some(Commands::SignMessage {
+            message,
+            signer,
+            scheme,
+            validator,
+            hex,
+        }) => {
+            let signer = decode_key(&signer, "--signer")?;
+            let message = read_message(message, hex || scheme == MessageScheme::Prehash)?;
+            let signed = message::sign(scheme, &message, validator, &signer)?;
+            println!("{}", serde_json::to_string_pretty(&signed)?);
+        }
+        Some(Commands::VerifyMessage {
+            message,
+            signature,
+            address,
+            scheme,
+            validator,
+            hex,
+        }) => {
+            let signature = hex::decode(signature.trim().trim_start_matches("0x"))?;
+            let message = read_message(message, hex || scheme == MessageScheme::Prehash)?;
+            let signer = message::recover(scheme, &message, validator, &signature)?;
+            if let Some(address) = address.filter(|address| *address != signer) {
+                Err(eyre!(
+                    "the message was signed by {signer}, not by {address}"
+                ))?;
+            }
+            println!("{signer}");
+        }
         None => Args::command().print_help().unwrap(),
     }
     Ok(())
 }
+
+/// Sends `bytes` with `eth_sendRawTransaction` and reports on stderr.
+///
+/// The hash returned by the node must match the locally computed hash. With a
+/// `timeout`, the receipt is awaited and the delegation code of each authority
+/// in `authorizations` is reported as well.
+fn broadcast(
+    client: &Client,
+    bytes: &[u8],
+    authorizations: &[Authorization],
+    timeout: Option<Duration>,
+) -> Result<()> {
+    let expected = keccak256(bytes);
+    let hash = client.send_raw_transaction(bytes)?;
+    eprintln!();
+    eprintln!("hash: {hash}");
+    if hash != expected {
+        Err(eyre!(
+            "the node returned hash {hash}, but the transaction hash is {expected}"
+        ))?;
+    }
+
+    let Some(timeout) = timeout else {
+        return Ok(());
+    };
+    let receipt = client.wait_for_receipt(hash, Duration::from_secs(1), timeout)?;
+    eprintln!("block: {}", receipt.block_number);
+    eprintln!(
+        "status: {}",
+        if receipt.status == U64::from(1) {
+            "success"
+        } else {
+            "reverted"
+        }
+    );
+    eprintln!("gas used: {}", receipt.gas_used);
+    for (i, auth) in authorizations.iter().enumerate() {
+        match auth.authority() {
+            Some(authority) => {
+                let code = client.code(authority)?;
+                eprintln!("authority {i} {authority}: {code}");
+            }
+            None => eprintln!("authority {i}: invalid signature"),
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the signature of the authorizations at `indices` with their
+/// high-s variant
+fn malleate_authorizations(tx: &mut Eip7702, indices: &[usize]) -> Result<()> {
+    for i in indices {
+        let auth = tx
+            .authorization_list
+            .get_mut(*i)
+            .ok_or(eyre!("there is no authorization {i}"))?;
+        let signature = auth
+            .signature
+            .take()
+            .ok_or(eyre!("authorization {i} is not signed"))?;
+        auth.signature = Some(signature.malleate());
+    }
+    Ok(())
+}
+
+/// Fails with every violation, one per line
+fn check(violations: Vec<Violation>, fork: Fork) -> Result<()> {
+    if violations.is_empty() {
+        return Ok(());
+    }
+    let violations = violations
+        .iter()
+        .map(|v| format!("\n  - {v}"))
+        .collect::<String>();
+    Err(eyre!(
+        "the transaction is invalid in {fork}, use `--no-validate` to encode it anyway:{violations}"
+    ))
+}
+
+/// Reads a transaction in hex encoding from `tx` or stdin
+fn read_tx(tx: Option<String>) -> Result<Vec<u8>> {
+    let tx = match tx {
+        Some(tx) => tx,
+        None => {
+            let mut tx = String::new();
+            let _ = io::stdin().read_to_string(&mut tx)?;
+            tx
+        }
+    };
+    Ok(hex::decode(tx.trim().trim_start_matches("0x"))?)
+}
+
+/// Reads a message from `message` or stdin, in hex encoding if `hex` is set
+fn read_message(message: Option<String>, hex: bool) -> Result<Vec<u8>> {
+    if hex {
+        return read_tx(message);
+    }
+    match message {
+        Some(message) => Ok(message.into_bytes()),
+        None => {
+            let mut message = Vec::new();
+            let _ = io::stdin().read_to_end(&mut message)?;
+            Ok(message)
+        }
+    }
+}
+
+/// Loads the functions of `--abi` files and `--signature`s, `None` if there
+/// are neither
+fn load_abi(abi: Vec<PathBuf>, signature: Vec<String>) -> Result<Option<Abi>> {
+    if abi.is_empty() && signature.is_empty() {
+        return Ok(None);
+    }
+    let mut functions = Abi::from_signatures(signature.iter().map(String::as_str))?;
+    for path in abi {
+        functions.extend(Abi::load(&path)?);
+    }
+    Ok(Some(functions))
+}
+
+/// Decodes a typed transaction into its json input format with a `type` field
+fn decode_tx(bytes: &[u8]) -> Result<Value> {
+    let (tx_type, payload) = bytes
+        .split_first()
+        .ok_or(eyre!("the transaction is empty"))?;
+    let mut json = match tx_type {
+        0x2 => serde_json::to_value(Eip1559::decode_bytes(payload)?)?,
+        0x4 => serde_json::to_value(Eip7702::decode_bytes(payload)?)?,
+        _ => Err(eyre!("invalid transaction type `{tx_type}`"))?,
+    };
+    if let Some(json) = json.as_object_mut() {
+        let _ = json.insert("type".into(), Value::from(*tx_type));
+    }
+    Ok(json)
+}
+
+/// Parses an `--arg` as json where possible and as a string otherwise
+fn parse_arg(arg: &str) -> Result<Value, String> {
+    Ok(serde_json::from_str(arg).unwrap_or_else(|_| Value::String(arg.to_string())))
+}
+
+/// Parses a `--nonce` of the form `ADDRESS=NONCE`
+fn parse_nonce(arg: &str) -> Result<(Address, U64), String> {
+    let (address, nonce) = arg
+        .split_once('=')
+        .ok_or(format!("expected `ADDRESS=NONCE`, got `{arg}`"))?;
+    let address = address.parse().map_err(|e| format!("{e}"))?;
+    let nonce = nonce.parse().map_err(|e| format!("{e}"))?;
+    Ok((address, nonce))
+}
+
+/// Decodes a private key in hex encoding `0x...` supplied with `flag`, which
+/// must be a nonzero scalar below the curve order
+fn decode_key(key: &str, flag: &str) -> Result<Vec<u8>> {
+    let key = hex::decode(key.trim().trim_start_matches("0x"))?;
+    if key.len() != 32 || SigningKey::from_slice(&key).is_err() {
+        Err(eyre!("a supplied `{flag}` is invalid"))?;
+    }
+    Ok(key)
+}
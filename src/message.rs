@@ -0,0 +1,188 @@
+//! Signing of off-chain messages in the formats of
+//! [EIP-191](https://eips.ethereum.org/EIPS/eip-191)
+
+use crate::transaction::{keccak256, recover_prehash, sign_prehash, signer_address, Signature};
+use alloy_primitives::{Address, B256, U256};
+use clap::ValueEnum;
+use color_eyre::eyre::{eyre, Result};
+use serde::Serialize;
+
+/// How a message is hashed before it is signed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum MessageScheme {
+    /// Version `0x45` as used by `personal_sign`,
+    /// `keccak256("\x19Ethereum Signed Message:\n" || len(message) || message)`
+    #[default]
+    Personal,
+    /// Version `0x00`, data with an intended validator,
+    /// `keccak256(0x19 || 0x00 || validator || message)`
+    Validator,
+    /// The message is a 32 byte hash that is signed as is
+    Prehash,
+}
+
+/// A signed message in the output format of `sign-message`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SignedMessage {
+    pub(crate) hash: B256,
+    pub(crate) signer: Address,
+    /// `r || s || v` with `v` in `{27, 28}`, as returned by `personal_sign`
+    pub(crate) signature: String,
+    #[serde(flatten)]
+    pub(crate) components: Signature,
+}
+
+/// The hash that is signed for `message`, `validator` is required by
+/// [`MessageScheme::Validator`] only
+pub(crate) fn hash(
+    scheme: MessageScheme,
+    message: &[u8],
+    validator: Option<Address>,
+) -> Result<B256> {
+    match scheme {
+        MessageScheme::Personal => {
+            let prefix = format!("\x19Ethereum Signed Message:\n{}", message.len());
+            Ok(keccak256(&[prefix.as_bytes(), message].concat()))
+        }
+        MessageScheme::Validator => {
+            let validator =
+                validator.ok_or(eyre!("a `--validator` is required for this scheme"))?;
+            Ok(keccak256(
+                &[&[0x19, 0x00], validator.as_slice(), message].concat(),
+            ))
+        }
+        MessageScheme::Prehash => {
+            if message.len() != 32 {
+                Err(eyre!(
+                    "a prehash must be 32 bytes, but the message has {}",
+                    message.len()
+                ))?;
+            }
+            Ok(B256::from_slice(message))
+        }
+    }
+}
+
+/// Signs `message` with the private key `signer`
+pub(crate) fn sign(
+    scheme: MessageScheme,
+    message: &[u8],
+    validator: Option<Address>,
+    signer: &[u8],
+) -> Result<SignedMessage> {
+    let hash = hash(scheme, message, validator)?;
+    let signature = sign_prehash(&hash, signer);
+    Ok(SignedMessage {
+        hash,
+        signer: signer_address(signer),
+        signature: format!("0x{}", hex::encode(to_bytes(&signature))),
+        components: signature,
+    })
+}
+
+/// Recovers the address that signed `message`
+pub(crate) fn recover(
+    scheme: MessageScheme,
+    message: &[u8],
+    validator: Option<Address>,
+    signature: &[u8],
+) -> Result<Address> {
+    let hash = hash(scheme, message, validator)?;
+    let signature = from_bytes(signature)?;
+    recover_prehash(&hash, &signature).ok_or(eyre!("the signature is invalid"))
+}
+
+/// Encodes `signature` as 65 bytes `r || s || v` with `v = 27 + y_parity`
+pub(crate) fn to_bytes(signature: &Signature) -> Vec<u8> {
+    [
+        signature.r.to_be_bytes::<32>().as_slice(),
+        signature.s.to_be_bytes::<32>().as_slice(),
+        &[27 + signature.y_parity as u8],
+    ]
+    .concat()
+}
+
+/// Decodes 65 bytes `r || s || v`, accepting `v` as `0`, `1`, `27` or `28`
+pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Signature> {
+    let [r @ .., v]: &[u8; 65] = bytes.try_into().map_err(|_| {
+        eyre!(
+            "a signature must be 65 bytes `r || s || v`, but it has {}",
+            bytes.len()
+        )
+    })?;
+    let y_parity = match v {
+        0 | 27 => false,
+        1 | 28 => true,
+        _ => Err(eyre!("invalid signature `v` {v}, expected 0, 1, 27 or 28"))?,
+    };
+    Ok(Signature {
+        y_parity,
+        r: U256::from_be_slice(&r[..32]),
+        s: U256::from_be_slice(&r[32..]),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signer() -> Vec<u8> {
+        hex::decode("34954993d403229ee2e01cf6fa8222224935bc47f9534b0c0ea8054764375501").unwrap()
+    }
+
+    #[test]
+    fn hashes_messages() {
+        // `hashMessage("hello world")` of ethers
+        assert_eq!(
+            hash(MessageScheme::Personal, b"hello world", None).unwrap(),
+            "0xd9eba16ed0ecae432b71fe008c98cc872bb4cc214d3220a36f365326cf807d68"
+                .parse::<B256>()
+                .unwrap()
+        );
+        let validator = signer_address(&signer());
+        assert_eq!(
+            hash(MessageScheme::Validator, b"data", Some(validator)).unwrap(),
+            keccak256(&[&[0x19, 0x00], validator.as_slice(), b"data"].concat())
+        );
+        assert!(hash(MessageScheme::Validator, b"data", None).is_err());
+        assert_eq!(
+            hash(MessageScheme::Prehash, &[0xAA; 32], None).unwrap(),
+            B256::from([0xAA; 32])
+        );
+        assert!(hash(MessageScheme::Prehash, &[0xAA; 31], None).is_err());
+    }
+
+    #[test]
+    fn signs_and_recovers_messages() {
+        let address = signer_address(&signer());
+        let validator = Some(Address::ZERO);
+        for scheme in MessageScheme::value_variants() {
+            let message = [0x42; 32];
+            let signed = sign(*scheme, &message, validator, &signer()).unwrap();
+            assert_eq!(signed.signer, address);
+
+            let bytes = hex::decode(&signed.signature[2..]).unwrap();
+            assert_eq!(from_bytes(&bytes).unwrap(), signed.components);
+            assert_eq!(
+                recover(*scheme, &message, validator, &bytes).unwrap(),
+                address
+            );
+            assert_ne!(
+                recover(*scheme, &[0x43; 32], validator, &bytes).ok(),
+                Some(address)
+            );
+        }
+
+        let signed = sign(MessageScheme::Personal, b"hi", None, &signer()).unwrap();
+        let mut bytes = hex::decode(&signed.signature[2..]).unwrap();
+        bytes[64] -= 27;
+        assert_eq!(
+            recover(MessageScheme::Personal, b"hi", None, &bytes).unwrap(),
+            address
+        );
+        bytes[64] = 2;
+        assert!(recover(MessageScheme::Personal, b"hi", None, &bytes).is_err());
+        assert!(recover(MessageScheme::Personal, b"hi", None, &bytes[..64]).is_err());
+    }
+}
@@ -0,0 +1,531 @@
+use crate::{
+    fork::Fork,
+    rlp::{long_header, Canonical, Decodable, EncodeHook, RlpItem},
+    transaction::{sign_payload, Eip1559, Eip7702, Signature, SECP256K1N},
+    validate::Violation,
+};
+use alloy_primitives::{Bytes, U256, U64};
+use color_eyre::eyre::{eyre, Result};
+use serde::Serialize;
+
+const CHAIN_ID: usize = 0;
+const NONCE: usize = 1;
+const MAX_PRIORITY_FEE_PER_GAS: usize = 2;
+const MAX_FEE_PER_GAS: usize = 3;
+const GAS_LIMIT: usize = 4;
+const DESTINATION: usize = 5;
+const DATA: usize = 7;
+const AUTHORIZATION_LIST: usize = 9;
+
+const Y_PARITY: usize = 0;
+const R: usize = 1;
+const S: usize = 2;
+
+/// A variant of a valid transaction that breaks one rule
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct Mutant {
+    pub(crate) name: &'static str,
+    /// Why a node rejects the transaction, or skips an authorization
+    pub(crate) reason: String,
+    pub(crate) tx: Bytes,
+}
+
+/// Writes the header of the item at a path in long form
+struct LongForm(Vec<usize>);
+
+impl EncodeHook for LongForm {
+    fn header(
+        &mut self,
+        path: &[usize],
+        item: &RlpItem,
+        payload: &[u8],
+        _: &[u8],
+    ) -> Option<Vec<u8>> {
+        (path == self.0).then(|| long_header(offset(item), payload.len()))
+    }
+}
+
+/// Pads the length of the item at a path with a leading zero byte
+struct PaddedLength(Vec<usize>);
+
+impl EncodeHook for PaddedLength {
+    fn header(
+        &mut self,
+        path: &[usize],
+        item: &RlpItem,
+        payload: &[u8],
+        _: &[u8],
+    ) -> Option<Vec<u8>> {
+        (path == self.0).then(|| {
+            let mut header = long_header(offset(item), payload.len());
+            header[0] += 1;
+            header.insert(1, 0);
+            header
+        })
+    }
+}
+
+/// Writes a length prefix for the single byte below `0x80` at a path
+struct PrefixedByte(Vec<usize>);
+
+impl EncodeHook for PrefixedByte {
+    fn header(&mut self, path: &[usize], _: &RlpItem, _: &[u8], _: &[u8]) -> Option<Vec<u8>> {
+        (path == self.0).then(|| vec![0x81])
+    }
+}
+
+fn offset(item: &RlpItem) -> u8 {
+    match item {
+        RlpItem::Data(_) => 0x80,
+        RlpItem::List(_) => 0xC0,
+    }
+}
+
+/// Collects the variants of one transaction
+struct Mutator<'a> {
+    tx_type: u8,
+    fields: Vec<RlpItem>,
+    signature: Vec<RlpItem>,
+    signer: Option<&'a [u8]>,
+    mutants: Vec<Mutant>,
+}
+
+impl Mutator<'_> {
+    /// Encodes `fields` with `hook`, signed again over the same encoding if
+    /// there is a signer
+    fn encode(&self, fields: Vec<RlpItem>, hook: &mut impl EncodeHook) -> Vec<u8> {
+        let signature = match self.signer {
+            Some(signer) => {
+                let payload = RlpItem::List(fields.clone()).encode_with(hook);
+                sign_payload(&payload, self.tx_type, signer.to_vec()).into()
+            }
+            None => self.signature.clone(),
+        };
+        let mut bytes = RlpItem::List([fields, signature].concat()).encode_with(hook);
+        bytes.insert(0, self.tx_type);
+        bytes
+    }
+
+    fn push(&mut self, name: &'static str, reason: impl Into<String>, tx: Vec<u8>) {
+        self.mutants.push(Mutant {
+            name,
+            reason: reason.into(),
+            tx: tx.into(),
+        });
+    }
+
+    /// A variant with changed fields
+    fn fields(
+        &mut self,
+        name: &'static str,
+        reason: impl Into<String>,
+        mutate: impl FnOnce(&mut Vec<RlpItem>),
+    ) {
+        let mut fields = self.fields.clone();
+        mutate(&mut fields);
+        let tx = self.encode(fields, &mut Canonical);
+        self.push(name, reason, tx);
+    }
+
+    /// A variant with a changed signature, which is never signed again
+    fn signature(
+        &mut self,
+        name: &'static str,
+        reason: impl Into<String>,
+        mutate: impl FnOnce(&mut Vec<RlpItem>),
+    ) {
+        let mut signature = self.signature.clone();
+        mutate(&mut signature);
+        let mut tx: Vec<u8> = RlpItem::List([self.fields.clone(), signature].concat()).into();
+        tx.insert(0, self.tx_type);
+        self.push(name, reason, tx);
+    }
+
+    /// A variant with a non-canonical encoding
+    fn encoding(&mut self, name: &'static str, reason: &str, mut hook: impl EncodeHook) {
+        let tx = self.encode(self.fields.clone(), &mut hook);
+        self.push(name, reason, tx);
+    }
+
+    /// A variant with changed bytes
+    fn bytes(&mut self, name: &'static str, reason: &str, mutate: impl FnOnce(&mut Vec<u8>)) {
+        let mut tx = self.encode(self.fields.clone(), &mut Canonical);
+        mutate(&mut tx);
+        self.push(name, reason, tx);
+    }
+
+    /// A variant with a changed first authorization
+    fn authorization(
+        &mut self,
+        name: &'static str,
+        reason: impl Into<String>,
+        mutate: impl FnOnce(&mut Vec<RlpItem>),
+    ) {
+        self.fields(name, reason, |fields| {
+            if let RlpItem::List(list) = &mut fields[AUTHORIZATION_LIST] {
+                if let Some(RlpItem::List(auth)) = list.first_mut() {
+                    mutate(auth);
+                }
+            }
+        });
+    }
+}
+
+/// Adds a leading zero byte to an integer
+fn leading_zero(item: &mut RlpItem) {
+    if let RlpItem::Data(data) = item {
+        data.insert(0, 0);
+    }
+}
+
+/// The items of the high-s variant of a signature, see [`Signature::malleate`]
+fn high_s(signature: &Signature) -> Vec<RlpItem> {
+    signature.clone().malleate().into()
+}
+
+/// Produces variants of the signed, valid transaction `bytes` that each break
+/// one rule, labelled with the reason a node rejects them.
+///
+/// With a `signer`, variants with changed fields are signed again so that
+/// they differ from `bytes` in one way only. Otherwise the original signature
+/// is kept, which recovers to a different sender.
+pub(crate) fn mutate(bytes: &[u8], signer: Option<&[u8]>, fork: Fork) -> Result<Vec<Mutant>> {
+    let (tx_type, payload) = bytes
+        .split_first()
+        .ok_or(eyre!("the transaction is empty"))?;
+    let ast = RlpItem::decode(payload)?;
+    let (fields, intrinsic_gas, tx_signature, auth_signature) = match tx_type {
+        0x2 => {
+            let tx = Eip1559::decode_bytes(payload)?;
+            (9, tx.intrinsic_gas(fork).minimum, tx.signature, None)
+        }
+        0x4 => {
+            let tx = Eip7702::decode_bytes(payload)?;
+            let auth_signature = tx
+                .authorization_list
+                .first()
+                .and_then(|auth| auth.signature.clone());
+            (
+                10,
+                tx.intrinsic_gas(fork).minimum,
+                tx.signature,
+                auth_signature,
+            )
+        }
+        _ => Err(eyre!("invalid transaction type `{tx_type}`"))?,
+    };
+    let RlpItem::List(mut items) = ast else {
+        Err(eyre!("the transaction is not a list"))?
+    };
+    let Some(tx_signature) = tx_signature else {
+        Err(eyre!("the transaction must be signed"))?
+    };
+    let signature = items.split_off(fields);
+    let mut m = Mutator {
+        tx_type: *tx_type,
+        fields: items,
+        signature,
+        signer,
+        mutants: Vec::new(),
+    };
+
+    // encoding
+    let other_type = if *tx_type == 0x2 { 0x4 } else { 0x2 };
+    m.bytes(
+        "wrong-type",
+        &format!("the payload is not a type {other_type} transaction"),
+        |tx| tx[0] = other_type,
+    );
+    m.bytes(
+        "truncated",
+        "the payload is shorter than its length prefix",
+        |tx| {
+            let _ = tx.pop();
+        },
+    );
+    m.bytes("trailing-bytes", "trailing bytes after the payload", |tx| {
+        tx.push(0x80)
+    });
+    m.encoding(
+        "long-form-length",
+        "a length below 56 bytes is encoded in long form",
+        LongForm(vec![DESTINATION]),
+    );
+    m.encoding(
+        "padded-length",
+        "a length has a leading zero byte",
+        PaddedLength(vec![]),
+    );
+    if let Some(i) = m
+        .fields
+        .iter()
+        .position(|item| matches!(item, RlpItem::Data(data) if data.len() == 1 && data[0] < 0x80))
+    {
+        m.encoding(
+            "prefixed-single-byte",
+            "a single byte below 0x80 is encoded with a length prefix",
+            PrefixedByte(vec![i]),
+        );
+    }
+    m.fields(
+        "leading-zero-nonce",
+        "integers must not have leading zeros",
+        |fields| leading_zero(&mut fields[NONCE]),
+    );
+    m.fields(
+        "extra-field",
+        format!(
+            "the transaction has {} fields instead of {}",
+            fields + 4,
+            fields + 3
+        ),
+        |fields| fields.push(RlpItem::Data(vec![])),
+    );
+    m.fields(
+        "missing-field",
+        format!(
+            "the transaction has {} fields instead of {}",
+            fields + 2,
+            fields + 3
+        ),
+        |fields| {
+            let _ = fields.pop();
+        },
+    );
+    m.fields("data-as-list", "`data` must be a string", |fields| {
+        fields[DATA] = RlpItem::List(vec![])
+    });
+    if !m.fields[DESTINATION].data()?.is_empty() {
+        m.fields(
+            "short-destination",
+            "`destination` must be 20 bytes",
+            |fields| fields[DESTINATION] = RlpItem::Data(vec![0xff; 19]),
+        );
+    }
+
+    // signature
+    m.signature("y-parity-2", "`yParity` must be 0 or 1", |signature| {
+        signature[Y_PARITY] = RlpItem::Data(vec![2])
+    });
+    m.signature(
+        "high-s",
+        "`s` must not be greater than secp256k1n/2 (EIP-2)",
+        |signature| *signature = high_s(&tx_signature),
+    );
+    m.signature("r-zero", "`r` must not be zero", |signature| {
+        signature[R] = U256::ZERO.into()
+    });
+    m.signature("s-zero", "`s` must not be zero", |signature| {
+        signature[S] = U256::ZERO.into()
+    });
+    m.signature(
+        "r-above-n",
+        "`r` must be less than secp256k1n",
+        |signature| signature[R] = SECP256K1N.into(),
+    );
+
+    // consensus rules
+    let chain_id = U64::try_from(m.fields[CHAIN_ID].clone())?;
+    // neither the transaction's chain id nor 0, and still a 64 bit integer
+    let wrong_chain_id = match chain_id {
+        U64::MAX => chain_id - U64::from(1),
+        _ => chain_id + U64::from(1),
+    };
+    m.fields(
+        "wrong-chain-id",
+        format!("chain id {wrong_chain_id} is not the network's"),
+        |fields| fields[CHAIN_ID] = wrong_chain_id.into(),
+    );
+    let max_fee = U256::try_from(m.fields[MAX_FEE_PER_GAS].clone())?;
+    m.fields(
+        "priority-fee-above-max-fee",
+        Violation::PriorityFeeAboveMaxFee {
+            priority_fee: max_fee + U256::from(1),
+            max_fee,
+        }
+        .to_string(),
+        |fields| fields[MAX_PRIORITY_FEE_PER_GAS] = (max_fee + U256::from(1)).into(),
+    );
+    m.fields(
+        "gas-limit-below-intrinsic",
+        Violation::IntrinsicGasTooLow {
+            gas_limit: U64::from(intrinsic_gas - 1),
+            intrinsic_gas,
+        }
+        .to_string(),
+        |fields| fields[GAS_LIMIT] = U64::from(intrinsic_gas - 1).into(),
+    );
+    m.fields("nonce-max", Violation::NonceMax.to_string(), |fields| {
+        fields[NONCE] = U64::MAX.into()
+    });
+
+    // authorizations
+    if *tx_type == 0x4 {
+        m.fields(
+            "empty-authorization-list",
+            Violation::EmptyAuthorizationList.to_string(),
+            |fields| fields[AUTHORIZATION_LIST] = RlpItem::List(vec![]),
+        );
+    }
+    if *tx_type == 0x4 && !m.fields[AUTHORIZATION_LIST].list()?.is_empty() {
+        let skipped = |reason: &str| format!("valid, but authorization 0 is skipped: {reason}");
+        m.authorization(
+            "authorization-extra-field",
+            "authorizations must have 6 fields",
+            |auth| auth.push(RlpItem::Data(vec![])),
+        );
+        m.authorization(
+            "authorization-short-address",
+            "authorization addresses must be 20 bytes",
+            |auth| auth[1] = RlpItem::Data(vec![0xff; 19]),
+        );
+        m.authorization(
+            "authorization-wrong-chain-id",
+            skipped("its chain id is neither 0 nor the transaction's"),
+            |auth| auth[0] = U256::from(wrong_chain_id).into(),
+        );
+        m.authorization(
+            "authorization-nonce-max",
+            skipped("its nonce is 2^64-1"),
+            |auth| {
+                auth[2] = match &auth[2] {
+                    RlpItem::List(_) => RlpItem::List(vec![U64::MAX.into()]),
+                    RlpItem::Data(_) => U64::MAX.into(),
+                }
+            },
+        );
+        // an unsigned authorization has no signature to break
+        if let Some(auth_signature) = auth_signature {
+            m.authorization(
+                "authorization-y-parity-2",
+                skipped("`yParity` must be 0 or 1"),
+                |auth| auth[3] = RlpItem::Data(vec![2]),
+            );
+            m.authorization(
+                "authorization-high-s",
+                skipped("`s` must not be greater than secp256k1n/2"),
+                |auth| auth.splice(3.., high_s(&auth_signature)).for_each(drop),
+            );
+        }
+    }
+
+    Ok(m.mutants)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::{signer_address, Eip7702Revision};
+
+    static EIP_7702_UNSIGNED: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/transactions/eip7702_unsigned.json"
+    ));
+
+    fn signer() -> Vec<u8> {
+        hex::decode("34954993d403229ee2e01cf6fa8222224935bc47f9534b0c0ea8054764375501").unwrap()
+    }
+
+    fn signed_7702() -> Vec<u8> {
+        let mut tx: Eip7702 = serde_json::from_str(EIP_7702_UNSIGNED).unwrap();
+        tx.authorization_list.truncate(1);
+        tx.gas_limit = U64::from(100000);
+        tx = tx.with_revision(Eip7702Revision::Prague).unwrap();
+        tx.authorization_list = vec![tx.authorization_list[0].clone().sign(signer())];
+        tx.sign(signer()).encode_typed()
+    }
+
+    fn find<'a>(mutants: &'a [Mutant], name: &str) -> &'a Mutant {
+        mutants.iter().find(|m| m.name == name).unwrap()
+    }
+
+    fn decode(tx: &[u8]) -> Eip7702 {
+        Eip7702::decode_bytes(&tx[1..]).unwrap()
+    }
+
+    #[test]
+    fn mutates_encoding() {
+        let tx = signed_7702();
+        let mutants = mutate(&tx, Some(&signer()), Fork::Prague).unwrap();
+
+        assert_eq!(find(&mutants, "wrong-type").tx[0], 2);
+        assert_eq!(find(&mutants, "truncated").tx.len(), tx.len() - 1);
+        assert_eq!(find(&mutants, "trailing-bytes").tx.len(), tx.len() + 1);
+
+        // the outer list is `0xf9 || len[2]`, padded to `0xfa 0x00 || len[2]`
+        let padded = &find(&mutants, "padded-length").tx;
+        assert_eq!(&padded[1..3], &[0xfa, 0x00]);
+
+        // the 20 byte destination is `0x94`, in long form `0xb8 0x14`
+        let destination = hex::decode("695461ef560fa4d3a3e7332c9bfcec261c11a1b6").unwrap();
+        let long = hex::encode(&find(&mutants, "long-form-length").tx);
+        assert!(long.contains(&format!("b814{}", hex::encode(&destination))));
+
+        // the chain id `0x01` is written as `0x81 0x01`
+        let prefixed = &find(&mutants, "prefixed-single-byte").tx;
+        assert_eq!(&prefixed[4..6], &[0x81, 0x01]);
+    }
+
+    #[test]
+    fn mutates_signatures() {
+        let tx = signed_7702();
+        let mutants = mutate(&tx, Some(&signer()), Fork::Prague).unwrap();
+        let original = decode(&tx).signature.unwrap();
+
+        let high_s = decode(&find(&mutants, "high-s").tx).signature.unwrap();
+        assert_eq!(high_s.r, original.r);
+        assert_eq!(high_s.s, SECP256K1N - original.s);
+        assert_eq!(high_s.y_parity, !original.y_parity);
+
+        let r_zero = decode(&find(&mutants, "r-zero").tx).signature.unwrap();
+        assert_eq!(r_zero.r, U256::ZERO);
+    }
+
+    #[test]
+    fn mutates_fields_and_signs_again() {
+        let tx = signed_7702();
+        let mutants = mutate(&tx, Some(&signer()), Fork::Prague).unwrap();
+
+        let nonce_max = decode(&find(&mutants, "nonce-max").tx);
+        assert_eq!(nonce_max.nonce, U64::MAX);
+        assert_eq!(nonce_max.sender(), Some(signer_address(&signer())));
+
+        let gas = find(&mutants, "gas-limit-below-intrinsic");
+        assert_eq!(
+            decode(&gas.tx).gas_limit,
+            U64::from(21000 + 4300 + 25000 - 1)
+        );
+        assert!(gas.reason.contains("below the intrinsic gas 50300"));
+
+        let empty = decode(&find(&mutants, "empty-authorization-list").tx);
+        assert!(empty.authorization_list.is_empty());
+
+        let wrong_chain = decode(&find(&mutants, "authorization-wrong-chain-id").tx);
+        assert_eq!(wrong_chain.authorization_list[0].chain_id, U256::from(2));
+        assert_eq!(wrong_chain.sender(), Some(signer_address(&signer())));
+
+        // without a signer the original signature is kept
+        let mutants = mutate(&tx, None, Fork::Prague).unwrap();
+        let nonce_max = decode(&find(&mutants, "nonce-max").tx);
+        assert_eq!(
+            nonce_max.signature.unwrap().r,
+            decode(&tx).signature.unwrap().r
+        );
+    }
+
+    #[test]
+    fn skips_signatures_of_unsigned_authorizations() {
+        let mut tx: Eip7702 = serde_json::from_str(EIP_7702_UNSIGNED).unwrap();
+        tx.authorization_list.truncate(1);
+        tx.gas_limit = U64::from(100000);
+        tx = tx.with_revision(Eip7702Revision::Prague).unwrap();
+        let tx = tx.sign(signer()).encode_typed();
+        assert!(decode(&tx).authorization_list[0].signature.is_none());
+
+        let mutants = mutate(&tx, Some(&signer()), Fork::Prague).unwrap();
+        assert!(mutants.iter().all(|m| m.name != "authorization-y-parity-2"));
+        assert!(mutants.iter().all(|m| m.name != "authorization-high-s"));
+        let nonce_max = decode(&find(&mutants, "authorization-nonce-max").tx);
+        assert_eq!(nonce_max.authorization_list[0].nonce, Some(U64::MAX));
+    }
+}
@@ -0,0 +1,186 @@
+use color_eyre::eyre::{eyre, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{fmt, str::FromStr};
+
+/// A single field override in the form `path=value`
+///
+/// Paths use the json field names and index into lists with brackets,
+/// e.g. `nonce`, `authorizationList[1].chainId` or
+/// `accessList[0].storageKeys[2]`.
+///
+/// The value is parsed as json if possible, so `5`, `true` and `null` keep
+/// their json types. Anything else, e.g. `0x3b9aca00`, is taken as a string.
+#[derive(Clone, Debug)]
+pub(crate) struct Override {
+    path: Vec<Segment>,
+    raw_path: String,
+    value: Value,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+impl fmt::Display for Override {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.raw_path)
+    }
+}
+
+impl FromStr for Override {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (raw_path, raw_value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected `path=value`, got `{s}`"))?;
+        let raw_path = raw_path.trim();
+        let raw_value = raw_value.trim();
+
+        let mut path = Vec::new();
+        for part in raw_path.split('.') {
+            let (key, mut indices) = match part.find('[') {
+                Some(i) => (&part[..i], &part[i..]),
+                None => (part, ""),
+            };
+            if key.is_empty() {
+                return Err(format!("invalid path `{raw_path}`"));
+            }
+            path.push(Segment::Key(key.to_string()));
+            while !indices.is_empty() {
+                let end = indices
+                    .find(']')
+                    .filter(|_| indices.starts_with('['))
+                    .ok_or_else(|| format!("invalid path `{raw_path}`"))?;
+                let index = indices[1..end]
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid index in path `{raw_path}`"))?;
+                path.push(Segment::Index(index));
+                indices = &indices[end + 1..];
+            }
+        }
+
+        let value = serde_json::from_str(raw_value)
+            .unwrap_or_else(|_| Value::String(raw_value.to_string()));
+
+        Ok(Override {
+            path,
+            raw_path: raw_path.to_string(),
+            value,
+        })
+    }
+}
+
+impl Override {
+    fn set(&self, root: &mut Value) -> Result<()> {
+        let mut target = root;
+        for segment in &self.path {
+            target = match segment {
+                Segment::Key(key) => target
+                    .as_object_mut()
+                    .and_then(|o| o.get_mut(key))
+                    .ok_or_else(|| eyre!("unknown field `{key}` in `--set {self}`"))?,
+                Segment::Index(index) => {
+                    let list = target
+                        .as_array_mut()
+                        .ok_or_else(|| eyre!("cannot index into a non-list in `--set {self}`"))?;
+                    let len = list.len();
+                    list.get_mut(*index).ok_or_else(|| {
+                        eyre!("index {index} is out of bounds (length {len}) in `--set {self}`")
+                    })?
+                }
+            };
+        }
+        *target = self.value.clone();
+        Ok(())
+    }
+}
+
+/// Applies `overrides` in order to a transaction (or any of its parts).
+///
+/// The value is round-tripped through json for every override so that each
+/// one is type-checked against the field it targets. Only fields present in
+/// the serialized form can be overridden, e.g. `r` on an unsigned
+/// transaction is an unknown field.
+pub(crate) fn apply<T: Serialize + DeserializeOwned>(
+    mut value: T,
+    overrides: &[Override],
+) -> Result<T> {
+    for o in overrides {
+        let mut json = serde_json::to_value(&value)?;
+        o.set(&mut json)?;
+        value = serde_json::from_value(json)
+            .map_err(|e| eyre!("invalid value for `--set {o}`: {e}"))?;
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::{Eip1559, Eip7702};
+    use alloy_primitives::{U256, U64};
+
+    static EIP_7702_UNSIGNED: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/transactions/eip7702_unsigned.json"
+    ));
+
+    fn parse(s: &str) -> Override {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_paths() {
+        let o = parse("authorizationList[1].chainId=0");
+        assert_eq!(
+            o.path,
+            vec![
+                Segment::Key("authorizationList".into()),
+                Segment::Index(1),
+                Segment::Key("chainId".into())
+            ]
+        );
+        assert_eq!(o.value, Value::from(0));
+
+        let o = parse("maxFeePerGas=0x3b9aca00");
+        assert_eq!(o.value, Value::from("0x3b9aca00"));
+
+        assert!("nonce".parse::<Override>().is_err());
+        assert!("=1".parse::<Override>().is_err());
+        assert!("accessList[x]=1".parse::<Override>().is_err());
+        assert!("accessList[0=1".parse::<Override>().is_err());
+    }
+
+    #[test]
+    fn applies_overrides() {
+        let tx: Eip7702 = serde_json::from_str(EIP_7702_UNSIGNED).unwrap();
+        let tx = apply(
+            tx,
+            &[
+                parse("nonce=5"),
+                parse("maxFeePerGas=0x3b9aca00"),
+                parse("authorizationList[1].chainId=0"),
+                parse("authorizationList[0].nonce=null"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(tx.nonce, U64::from(5));
+        assert_eq!(tx.max_fee_per_gas, U256::from(0x3b9aca00u64));
+        assert_eq!(tx.authorization_list[1].chain_id, U256::ZERO);
+        assert!(tx.authorization_list[0].nonce.is_none());
+    }
+
+    #[test]
+    fn rejects_bad_overrides() {
+        let tx = Eip1559::default();
+        assert!(apply(tx.clone(), &[parse("nonse=1")]).is_err());
+        assert!(apply(tx.clone(), &[parse("nonce=-1")]).is_err());
+        assert!(apply(tx.clone(), &[parse("destination=0x1234")]).is_err());
+        assert!(apply(tx.clone(), &[parse("accessList[0].address=0x")]).is_err());
+        assert!(apply(tx, &[parse("nonce.inner=1")]).is_err());
+    }
+}
@@ -0,0 +1,170 @@
+//! Deserializes integer fields the same way from every input format: a json
+//! number, a decimal string or a `0x` hex string, failing on overflow,
+//! negative numbers and anything else.
+//!
+//! Numbers above `u64::MAX` lose precision in json, so larger values must be
+//! strings, e.g. `"115792089237316195423570985008687907853269984665640564039457584007913129639935"`.
+
+use alloy_primitives::Uint;
+use serde::{
+    de::{self, Visitor},
+    Deserializer,
+};
+use std::{fmt, marker::PhantomData};
+
+/// Parses a decimal or `0x` hex string
+pub(crate) fn parse<const BITS: usize, const LIMBS: usize>(
+    s: &str,
+) -> Result<Uint<BITS, LIMBS>, String> {
+    let (digits, radix) = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => (hex, 16),
+        None => (s, 10),
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix as u32)) {
+        return Err(format!("`{s}` is not a decimal or hex integer"));
+    }
+    Uint::from_str_radix(digits, radix).map_err(|_| format!("`{s}` does not fit in {BITS} bits"))
+}
+
+struct QuantityVisitor<const BITS: usize, const LIMBS: usize>;
+
+impl<'de, const BITS: usize, const LIMBS: usize> Visitor<'de> for QuantityVisitor<BITS, LIMBS> {
+    type Value = Uint<BITS, LIMBS>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "a {BITS} bit integer as a number, a decimal string or a hex string"
+        )
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Uint::try_from(v).map_err(|_| E::custom(format!("`{v}` does not fit in {BITS} bits")))
+    }
+
+    fn visit_u128<E: de::Error>(self, v: u128) -> Result<Self::Value, E> {
+        Uint::try_from(v).map_err(|_| E::custom(format!("`{v}` does not fit in {BITS} bits")))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        match u64::try_from(v) {
+            Ok(v) => self.visit_u64(v),
+            Err(_) => Err(E::custom(format!("`{v}` is negative"))),
+        }
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        Err(E::custom(format!(
+            "`{v}` is not an integer or too large for a number, write it as a decimal or hex string"
+        )))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        parse(v).map_err(E::custom)
+    }
+}
+
+/// For `#[serde(deserialize_with = "quantity::deserialize")]`
+pub(crate) fn deserialize<'de, D: Deserializer<'de>, const BITS: usize, const LIMBS: usize>(
+    deserializer: D,
+) -> Result<Uint<BITS, LIMBS>, D::Error> {
+    deserializer.deserialize_any(QuantityVisitor)
+}
+
+struct OptionVisitor<const BITS: usize, const LIMBS: usize>(PhantomData<Uint<BITS, LIMBS>>);
+
+impl<'de, const BITS: usize, const LIMBS: usize> Visitor<'de> for OptionVisitor<BITS, LIMBS> {
+    type Value = Option<Uint<BITS, LIMBS>>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        QuantityVisitor::<BITS, LIMBS>.expecting(f)?;
+        write!(f, " or null")
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserialize(deserializer).map(Some)
+    }
+}
+
+/// For `#[serde(default, deserialize_with = "quantity::option")]`
+pub(crate) fn option<'de, D: Deserializer<'de>, const BITS: usize, const LIMBS: usize>(
+    deserializer: D,
+) -> Result<Option<Uint<BITS, LIMBS>>, D::Error> {
+    deserializer.deserialize_option(OptionVisitor(PhantomData))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{U256, U64};
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize)]
+    struct Fields {
+        #[serde(deserialize_with = "deserialize")]
+        small: U64,
+        #[serde(deserialize_with = "deserialize")]
+        large: U256,
+        #[serde(default, deserialize_with = "option")]
+        optional: Option<U64>,
+    }
+
+    fn fields(small: serde_json::Value, large: serde_json::Value) -> Result<Fields, String> {
+        serde_json::from_value(json!({ "small": small, "large": large })).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn accepts_numbers_and_strings() {
+        for (small, large) in [
+            (json!(10), json!(10)),
+            (json!("10"), json!("10")),
+            (json!("0xa"), json!("0XA")),
+            (json!("0x000a"), json!("0x0a")),
+        ] {
+            let parsed = fields(small, large).unwrap();
+            assert_eq!(parsed.small, U64::from(10));
+            assert_eq!(parsed.large, U256::from(10));
+            assert_eq!(parsed.optional, None);
+        }
+
+        let max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+        let parsed = fields(json!(u64::MAX), json!(max)).unwrap();
+        assert_eq!(parsed.small, U64::MAX);
+        assert_eq!(parsed.large, U256::MAX);
+
+        let parsed: Fields =
+            serde_json::from_value(json!({ "small": 0, "large": 0, "optional": "0x1" })).unwrap();
+        assert_eq!(parsed.optional, Some(U64::from(1)));
+    }
+
+    #[test]
+    fn rejects_invalid_integers() {
+        let error = fields(json!("18446744073709551616"), json!(0)).unwrap_err();
+        assert!(error.contains("does not fit in 64 bits"), "{error}");
+        let error =
+            serde_json::from_str::<Fields>(r#"{"small": 0, "large": 18446744073709551616}"#)
+                .unwrap_err()
+                .to_string();
+        assert!(
+            error.contains("write it as a decimal or hex string"),
+            "{error}"
+        );
+        let error = fields(json!(-1), json!(0)).unwrap_err();
+        assert!(error.contains("is negative"), "{error}");
+
+        for invalid in ["", "0x", " 1", "1_000", "1e3", "0xg"] {
+            let error = fields(json!(0), json!(invalid)).unwrap_err();
+            assert!(error.contains("is not a decimal or hex integer"), "{error}");
+        }
+        assert!(fields(json!(0), json!(format!("0x1{}", "0".repeat(64)))).is_err());
+    }
+}
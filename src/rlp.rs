@@ -1,6 +1,15 @@
-use alloy_primitives::{Bytes, U256, U64};
-use core::panic;
-use std::{collections::VecDeque, fmt};
+use alloy_primitives::{Address, Bytes, FixedBytes, U256, U64};
+use bytes::BufMut;
+use color_eyre::eyre::{eyre, Result};
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    ser::SerializeSeq,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{
+    fmt,
+    io::{ErrorKind, Read},
+};
 
 #[derive(Clone)]
 pub(crate) enum RlpItem {
@@ -8,19 +17,18 @@ pub(crate) enum RlpItem {
     List(Vec<RlpItem>),
 }
 
-#[allow(dead_code)]
 impl RlpItem {
-    pub(crate) fn data(&self) -> &[u8] {
+    pub(crate) fn data(&self) -> Result<&[u8]> {
         match self {
-            RlpItem::Data(data) => data,
-            _ => panic!("not data"),
+            RlpItem::Data(data) => Ok(data),
+            _ => Err(eyre!("expected data, got a list")),
         }
     }
 
-    pub(crate) fn list(&self) -> &[RlpItem] {
+    pub(crate) fn list(&self) -> Result<&[RlpItem]> {
         match self {
-            RlpItem::List(list) => list,
-            _ => panic!("not a list"),
+            RlpItem::List(list) => Ok(list),
+            _ => Err(eyre!("expected a list, got data")),
         }
     }
 }
@@ -37,12 +45,14 @@ impl From<bool> for RlpItem {
     }
 }
 
-impl From<RlpItem> for bool {
-    fn from(value: RlpItem) -> Self {
-        match value.data() {
-            [0x1] => true,
-            [] => false,
-            _ => panic!("invalid boolean value"),
+impl TryFrom<RlpItem> for bool {
+    type Error = color_eyre::Report;
+
+    fn try_from(value: RlpItem) -> Result<Self> {
+        match value.data()? {
+            [0x1] => Ok(true),
+            [] => Ok(false),
+            _ => Err(eyre!("invalid boolean value")),
         }
     }
 }
@@ -59,9 +69,11 @@ impl From<U64> for RlpItem {
     }
 }
 
-impl From<RlpItem> for U64 {
-    fn from(value: RlpItem) -> Self {
-        U64::from_be_slice(value.data())
+impl TryFrom<RlpItem> for U64 {
+    type Error = color_eyre::Report;
+
+    fn try_from(value: RlpItem) -> Result<Self> {
+        U64::try_from_be_slice(value.data()?).ok_or(eyre!("an integer of more than 64 bits"))
     }
 }
 
@@ -77,9 +89,11 @@ impl From<U256> for RlpItem {
     }
 }
 
-impl From<RlpItem> for U256 {
-    fn from(value: RlpItem) -> Self {
-        U256::from_be_slice(value.data())
+impl TryFrom<RlpItem> for U256 {
+    type Error = color_eyre::Report;
+
+    fn try_from(value: RlpItem) -> Result<Self> {
+        U256::try_from_be_slice(value.data()?).ok_or(eyre!("an integer of more than 256 bits"))
     }
 }
 
@@ -89,94 +103,695 @@ impl From<Bytes> for RlpItem {
     }
 }
 
+impl From<Address> for RlpItem {
+    fn from(value: Address) -> Self {
+        value.as_slice().into()
+    }
+}
+
+/// `None` is encoded as empty data, e.g. the `destination` of a contract
+/// creation
+impl From<Option<Address>> for RlpItem {
+    fn from(value: Option<Address>) -> Self {
+        value.map_or(RlpItem::Data(vec![]), Into::into)
+    }
+}
+
+impl From<FixedBytes<32>> for RlpItem {
+    fn from(value: FixedBytes<32>) -> Self {
+        value.as_slice().into()
+    }
+}
+
+impl<T: Into<RlpItem>> From<Vec<T>> for RlpItem {
+    fn from(value: Vec<T>) -> Self {
+        RlpItem::List(value.into_iter().map(Into::into).collect())
+    }
+}
+
 impl From<RlpItem> for Vec<u8> {
     fn from(value: RlpItem) -> Self {
-        let mut bytes = Vec::new();
-        match value {
-            RlpItem::Data(mut data) => match data.len() {
-                1 if data[0] <= 0x7F => {
-                    bytes.push(data[0]);
+        value.encode_with(&mut Canonical)
+    }
+}
+
+/// Hooks into the encoding of every item, e.g. to write non-canonical headers
+pub(crate) trait EncodeHook {
+    /// Returns the header to write for the item at `path`, the indices of the
+    /// item in its enclosing lists, or `None` to write the canonical `header`.
+    /// `payload` is the item's data, or the encoded items of a list.
+    fn header(
+        &mut self,
+        path: &[usize],
+        item: &RlpItem,
+        payload: &[u8],
+        header: &[u8],
+    ) -> Option<Vec<u8>>;
+}
+
+/// The canonical encoding, without any hooks
+pub(crate) struct Canonical;
+
+impl EncodeHook for Canonical {
+    fn header(&mut self, _: &[usize], _: &RlpItem, _: &[u8], _: &[u8]) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+/// The header of a payload of `len` bytes, `offset` is `0x80` for data and
+/// `0xC0` for lists
+pub(crate) fn header(offset: u8, len: usize) -> Vec<u8> {
+    match len {
+        0..=55 => vec![offset + len as u8],
+        56.. => long_header(offset, len),
+    }
+}
+
+/// The long form of [`header`], which is only canonical from 56 bytes
+pub(crate) fn long_header(offset: u8, len: usize) -> Vec<u8> {
+    let mut len = len
+        .to_be_bytes()
+        .into_iter()
+        .skip_while(|b| *b == 0x0)
+        .collect::<Vec<_>>();
+    if len.is_empty() {
+        len.push(0);
+    }
+    let mut bytes = vec![offset + 55 + len.len() as u8];
+    bytes.append(&mut len);
+    bytes
+}
+
+/// Encodes directly into a buffer, without building an [`RlpItem`] tree.
+///
+/// `length` is computed up front so that list headers can be written before
+/// their items.
+pub(crate) trait Encodable {
+    /// Writes the encoding, header included, into `out`
+    fn encode(&self, out: &mut impl BufMut);
+
+    /// The length of the encoding, header included
+    fn length(&self) -> usize;
+
+    /// The encoding in a buffer of exactly [`Encodable::length`]
+    fn encoded(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.length());
+        self.encode(&mut out);
+        out
+    }
+}
+
+/// The length of the header of a payload of `len` bytes
+pub(crate) fn header_length(len: usize) -> usize {
+    match len {
+        0..=55 => 1,
+        _ => 1 + (usize::BITS - len.leading_zeros()).div_ceil(8) as usize,
+    }
+}
+
+/// Writes the canonical header of a payload of `len` bytes, `offset` is
+/// `0x80` for data and `0xC0` for lists
+pub(crate) fn encode_header(offset: u8, len: usize, out: &mut impl BufMut) {
+    match len {
+        0..=55 => out.put_u8(offset + len as u8),
+        _ => {
+            let len_len = header_length(len) - 1;
+            out.put_u8(offset + 55 + len_len as u8);
+            out.put_slice(&len.to_be_bytes()[size_of::<usize>() - len_len..]);
+        }
+    }
+}
+
+/// The length of `payload` encoded as data
+fn data_length(payload: &[u8]) -> usize {
+    match payload {
+        [byte] if *byte <= 0x7F => 1,
+        _ => header_length(payload.len()) + payload.len(),
+    }
+}
+
+fn encode_data(payload: &[u8], out: &mut impl BufMut) {
+    if !matches!(payload, [byte] if *byte <= 0x7F) {
+        encode_header(0x80, payload.len(), out);
+    }
+    out.put_slice(payload);
+}
+
+/// Strips the leading zeros of a big endian integer
+fn trim(bytes: &[u8]) -> &[u8] {
+    let zeros = bytes.iter().take_while(|b| **b == 0).count();
+    &bytes[zeros..]
+}
+
+impl Encodable for [u8] {
+    fn encode(&self, out: &mut impl BufMut) {
+        encode_data(self, out)
+    }
+
+    fn length(&self) -> usize {
+        data_length(self)
+    }
+}
+
+impl Encodable for bool {
+    fn encode(&self, out: &mut impl BufMut) {
+        out.put_u8(if *self { 0x1 } else { 0x80 })
+    }
+
+    fn length(&self) -> usize {
+        1
+    }
+}
+
+impl Encodable for U64 {
+    fn encode(&self, out: &mut impl BufMut) {
+        encode_data(trim(&self.to_be_bytes::<8>()), out)
+    }
+
+    fn length(&self) -> usize {
+        data_length(trim(&self.to_be_bytes::<8>()))
+    }
+}
+
+impl Encodable for U256 {
+    fn encode(&self, out: &mut impl BufMut) {
+        encode_data(trim(&self.to_be_bytes::<32>()), out)
+    }
+
+    fn length(&self) -> usize {
+        data_length(trim(&self.to_be_bytes::<32>()))
+    }
+}
+
+impl Encodable for Bytes {
+    fn encode(&self, out: &mut impl BufMut) {
+        encode_data(self, out)
+    }
+
+    fn length(&self) -> usize {
+        data_length(self)
+    }
+}
+
+impl Encodable for Address {
+    fn encode(&self, out: &mut impl BufMut) {
+        encode_data(self.as_slice(), out)
+    }
+
+    fn length(&self) -> usize {
+        21
+    }
+}
+
+/// `None` is encoded as empty data, as in `From<Option<Address>>`
+impl Encodable for Option<Address> {
+    fn encode(&self, out: &mut impl BufMut) {
+        encode_data(self.as_ref().map_or(&[], |a| a.as_slice()), out)
+    }
+
+    fn length(&self) -> usize {
+        self.as_ref().map_or(1, Encodable::length)
+    }
+}
+
+impl Encodable for FixedBytes<32> {
+    fn encode(&self, out: &mut impl BufMut) {
+        encode_data(self.as_slice(), out)
+    }
+
+    fn length(&self) -> usize {
+        33
+    }
+}
+
+impl<T: Encodable> Encodable for Vec<T> {
+    fn encode(&self, out: &mut impl BufMut) {
+        encode_header(0xC0, self.iter().map(Encodable::length).sum(), out);
+        for item in self {
+            item.encode(out);
+        }
+    }
+
+    fn length(&self) -> usize {
+        let payload = self.iter().map(Encodable::length).sum();
+        header_length(payload) + payload
+    }
+}
+
+impl Encodable for RlpItem {
+    fn encode(&self, out: &mut impl BufMut) {
+        match self {
+            RlpItem::Data(data) => encode_data(data, out),
+            RlpItem::List(list) => list.encode(out),
+        }
+    }
+
+    fn length(&self) -> usize {
+        match self {
+            RlpItem::Data(data) => data_length(data),
+            RlpItem::List(list) => list.length(),
+        }
+    }
+}
+
+impl RlpItem {
+    /// Encodes this item, letting `hook` replace the header of any item
+    pub(crate) fn encode_with(&self, hook: &mut impl EncodeHook) -> Vec<u8> {
+        fn encode(item: &RlpItem, path: &mut Vec<usize>, hook: &mut impl EncodeHook) -> Vec<u8> {
+            let (payload, canonical) = match item {
+                RlpItem::Data(data) => match data.len() {
+                    1 if data[0] <= 0x7F => (data.clone(), vec![]),
+                    len => (data.clone(), header(0x80, len)),
+                },
+                RlpItem::List(list) => {
+                    let mut encoded = Vec::new();
+                    for (i, item) in list.iter().enumerate() {
+                        path.push(i);
+                        encoded.append(&mut encode(item, path, hook));
+                        let _ = path.pop();
+                    }
+                    let header = header(0xC0, encoded.len());
+                    (encoded, header)
                 }
-                0..=55 => {
-                    bytes.push(0x80 + data.len() as u8);
-                    bytes.append(&mut data);
+            };
+            let mut bytes = hook
+                .header(path, item, &payload, &canonical)
+                .unwrap_or(canonical);
+            bytes.extend(payload);
+            bytes
+        }
+        encode(self, &mut Vec::new(), hook)
+    }
+}
+
+/// An item borrowed from the input with its position, for inspection.
+///
+/// Only the header is decoded, the items of a list are decoded one at a time
+/// by [`RlpView::items`], so nothing is copied.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct RlpView<'a> {
+    /// The offset of the header in the input
+    pub(crate) offset: usize,
+    /// The prefix byte and any length bytes, empty for a single byte below
+    /// `0x80`
+    pub(crate) header: &'a [u8],
+    pub(crate) payload: &'a [u8],
+    pub(crate) is_list: bool,
+}
+
+impl<'a> RlpView<'a> {
+    /// Decodes the header of the item at the start of `bytes`, where `offset`
+    /// is the position of `bytes` in the input. This fails on malformed input
+    /// and keeps non-canonical headers.
+    pub(crate) fn parse(bytes: &'a [u8], offset: usize) -> Result<RlpView<'a>> {
+        let first = *bytes
+            .first()
+            .ok_or(eyre!("expected an item at offset {offset}"))?;
+        let (header_len, len) = match first {
+            0x00..=0x7F => (0, 1),
+            0x80..=0xB7 => (1, (first - 0x80) as usize),
+            0xC0..=0xF7 => (1, (first - 0xC0) as usize),
+            0xB8..=0xBF | 0xF8..=0xFF => {
+                let len_len = (first - if first < 0xC0 { 0xB7 } else { 0xF7 }) as usize;
+                let len = bytes
+                    .get(1..1 + len_len)
+                    .ok_or(eyre!("the length at offset {offset} is truncated"))?;
+                if len_len > 8 {
+                    Err(eyre!("the length at offset {offset} is too large"))?;
                 }
-                56.. => {
-                    let mut len = data
-                        .len()
-                        .to_be_bytes()
-                        .into_iter()
-                        .skip_while(|b| *b == 0x0)
-                        .collect::<Vec<_>>();
-                    bytes.push(0xB7 + len.len() as u8);
-                    bytes.append(&mut len);
-                    bytes.append(&mut data);
+                let len = len.iter().fold(0u64, |a, b| a << 8 | *b as u64);
+                (1 + len_len, usize::try_from(len)?)
+            }
+        };
+        let end = header_len
+            .checked_add(len)
+            .filter(|end| *end <= bytes.len())
+            .ok_or(eyre!(
+                "the item at offset {offset} has {len} bytes, but only {} remain",
+                bytes.len() - header_len
+            ))?;
+        Ok(RlpView {
+            offset,
+            header: &bytes[..header_len],
+            payload: &bytes[header_len..end],
+            is_list: first >= 0xC0,
+        })
+    }
+
+    /// Decodes `bytes` as exactly one item, failing on trailing bytes
+    pub(crate) fn parse_exact(bytes: &'a [u8]) -> Result<RlpView<'a>> {
+        let view = RlpView::parse(bytes, 0)?;
+        if view.len() < bytes.len() {
+            Err(eyre!(
+                "{} trailing bytes after the item",
+                bytes.len() - view.len()
+            ))?;
+        }
+        Ok(view)
+    }
+
+    /// The items of a list, decoded as they are iterated, or no items for
+    /// data
+    pub(crate) fn items(&self) -> RlpViews<'a> {
+        RlpViews {
+            bytes: if self.is_list { self.payload } else { &[] },
+            offset: self.offset + self.header.len(),
+        }
+    }
+
+    /// The length of the header and payload
+    pub(crate) fn len(&self) -> usize {
+        self.header.len() + self.payload.len()
+    }
+
+    /// Whether the header is the shortest encoding of the payload
+    pub(crate) fn is_canonical(&self) -> bool {
+        let canonical = match (self.is_list, self.payload) {
+            (true, payload) => header(0xC0, payload.len()),
+            (false, [byte]) if *byte <= 0x7F => vec![],
+            (false, payload) => header(0x80, payload.len()),
+        };
+        self.header == canonical
+    }
+}
+
+/// The items of an [`RlpView`] list, which stops after the first malformed
+/// item
+pub(crate) struct RlpViews<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Iterator for RlpViews<'a> {
+    type Item = Result<RlpView<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.bytes.is_empty() {
+            return None;
+        }
+        match RlpView::parse(self.bytes, self.offset) {
+            Ok(view) => {
+                self.bytes = &self.bytes[view.len()..];
+                self.offset += view.len();
+                Some(Ok(view))
+            }
+            Err(e) => {
+                self.bytes = &[];
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl TryFrom<RlpView<'_>> for RlpItem {
+    type Error = color_eyre::Report;
+
+    fn try_from(value: RlpView<'_>) -> Result<Self> {
+        match value.is_list {
+            true => Ok(RlpItem::List(
+                value
+                    .items()
+                    .map(|item| item.and_then(RlpItem::try_from))
+                    .collect::<Result<_>>()?,
+            )),
+            false => Ok(RlpItem::Data(value.payload.to_vec())),
+        }
+    }
+}
+
+impl RlpItem {
+    /// Decodes `bytes` as exactly one item, failing on malformed input or
+    /// trailing bytes
+    pub(crate) fn decode(bytes: &[u8]) -> Result<RlpItem> {
+        RlpView::parse_exact(bytes)?.try_into()
+    }
+}
+
+/// Decodes from a borrowed [`RlpView`], failing instead of panicking on
+/// malformed input.
+///
+/// Unlike the `TryFrom<RlpItem>` conversions this only accepts canonical
+/// encodings: the shortest headers and integers without leading zeros, so
+/// that a decoded value encodes back to the same bytes.
+pub(crate) trait Decodable: Sized {
+    /// Decodes `view` and any items it contains
+    fn decode(view: RlpView<'_>) -> Result<Self>;
+
+    /// Decodes `bytes` as exactly one item
+    fn decode_bytes(bytes: &[u8]) -> Result<Self> {
+        Self::decode(RlpView::parse_exact(bytes)?)
+    }
+}
+
+/// The payload of canonical data
+fn data<'a>(view: &RlpView<'a>) -> Result<&'a [u8]> {
+    if view.is_list {
+        Err(eyre!("expected data at offset {}, got a list", view.offset))?;
+    }
+    if !view.is_canonical() {
+        Err(eyre!("non-canonical header at offset {}", view.offset))?;
+    }
+    Ok(view.payload)
+}
+
+/// The payload of a canonical integer of at most `N` bytes
+fn integer<'a, const N: usize>(view: &RlpView<'a>) -> Result<&'a [u8]> {
+    let payload = data(view)?;
+    if payload.len() > N {
+        Err(eyre!(
+            "the integer at offset {} has {} bytes, at most {N} are allowed",
+            view.offset,
+            payload.len()
+        ))?;
+    }
+    if payload.first() == Some(&0) {
+        Err(eyre!(
+            "the integer at offset {} has leading zeros",
+            view.offset
+        ))?;
+    }
+    Ok(payload)
+}
+
+/// The payload of data of exactly `N` bytes
+fn fixed<'a, const N: usize>(view: &RlpView<'a>) -> Result<&'a [u8]> {
+    let payload = data(view)?;
+    if payload.len() != N {
+        Err(eyre!(
+            "expected {N} bytes at offset {}, got {}",
+            view.offset,
+            payload.len()
+        ))?;
+    }
+    Ok(payload)
+}
+
+impl Decodable for bool {
+    fn decode(view: RlpView<'_>) -> Result<Self> {
+        match data(&view)? {
+            [0x1] => Ok(true),
+            [] => Ok(false),
+            _ => Err(eyre!("invalid boolean value at offset {}", view.offset)),
+        }
+    }
+}
+
+impl Decodable for U64 {
+    fn decode(view: RlpView<'_>) -> Result<Self> {
+        Ok(U64::from_be_slice(integer::<8>(&view)?))
+    }
+}
+
+impl Decodable for U256 {
+    fn decode(view: RlpView<'_>) -> Result<Self> {
+        Ok(U256::from_be_slice(integer::<32>(&view)?))
+    }
+}
+
+impl Decodable for Bytes {
+    fn decode(view: RlpView<'_>) -> Result<Self> {
+        Ok(data(&view)?.to_vec().into())
+    }
+}
+
+impl Decodable for Address {
+    fn decode(view: RlpView<'_>) -> Result<Self> {
+        Ok(Address::from_slice(fixed::<20>(&view)?))
+    }
+}
+
+/// Empty data is `None`, as in `From<Option<Address>> for RlpItem`
+impl Decodable for Option<Address> {
+    fn decode(view: RlpView<'_>) -> Result<Self> {
+        match data(&view)?.is_empty() {
+            true => Ok(None),
+            false => Address::decode(view).map(Some),
+        }
+    }
+}
+
+impl Decodable for FixedBytes<32> {
+    fn decode(view: RlpView<'_>) -> Result<Self> {
+        Ok(FixedBytes::from_slice(fixed::<32>(&view)?))
+    }
+}
+
+impl<T: Decodable> Decodable for Vec<T> {
+    fn decode(view: RlpView<'_>) -> Result<Self> {
+        list(&view)?
+            .items()
+            .map(|item| item.and_then(T::decode))
+            .collect()
+    }
+}
+
+/// Checks that `view` is a list with a canonical header, for derived
+/// [`Decodable`] implementations
+pub(crate) fn list<'a, 'b>(view: &'b RlpView<'a>) -> Result<&'b RlpView<'a>> {
+    if !view.is_list {
+        Err(eyre!("expected a list at offset {}, got data", view.offset))?;
+    }
+    if !view.is_canonical() {
+        Err(eyre!("non-canonical header at offset {}", view.offset))?;
+    }
+    Ok(view)
+}
+
+/// Reads concatenated top-level items from a reader one at a time, so that
+/// only the current item is held in memory.
+///
+/// Each item is yielded as its encoding, header included, to be decoded with
+/// [`RlpView`] or [`RlpItem::decode`]. Iteration stops after the first
+/// malformed or truncated item.
+pub(crate) struct RlpReader<R> {
+    reader: R,
+    /// The offset of the next item in the input
+    pub(crate) offset: usize,
+    done: bool,
+}
+
+impl<R: Read> RlpReader<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        RlpReader {
+            reader,
+            offset: 0,
+            done: false,
+        }
+    }
+
+    /// Reads the rest of the item starting with `first`
+    fn read_item(&mut self, first: u8) -> Result<Vec<u8>> {
+        let offset = self.offset;
+        let mut item = vec![first];
+        let len = match first {
+            0x00..=0x7F => 0,
+            0x80..=0xB7 => (first - 0x80) as u64,
+            0xC0..=0xF7 => (first - 0xC0) as u64,
+            0xB8..=0xBF | 0xF8..=0xFF => {
+                let len_len = (first - if first < 0xC0 { 0xB7 } else { 0xF7 }) as u64;
+                if len_len > 8 {
+                    Err(eyre!("the length at offset {offset} is too large"))?;
+                }
+                if self.read(len_len, &mut item)? < len_len {
+                    Err(eyre!("the length at offset {offset} is truncated"))?;
                 }
-            },
+                item[1..].iter().fold(0u64, |a, b| a << 8 | *b as u64)
+            }
+        };
+        let read = self.read(len, &mut item)?;
+        if read < len {
+            Err(eyre!(
+                "the item at offset {offset} has {len} bytes, but only {read} remain"
+            ))?;
+        }
+        self.offset += item.len();
+        Ok(item)
+    }
+
+    /// Appends up to `len` bytes to `out`, fewer at the end of the input
+    fn read(&mut self, len: u64, out: &mut Vec<u8>) -> Result<u64> {
+        Ok(self.reader.by_ref().take(len).read_to_end(out)? as u64)
+    }
+}
+
+impl<R: Read> Iterator for RlpReader<R> {
+    type Item = Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let mut first = [0];
+        let item = match self.reader.read_exact(&mut first) {
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => None,
+            Err(e) => Some(Err(e.into())),
+            Ok(()) => Some(self.read_item(first[0])),
+        };
+        self.done = !matches!(item, Some(Ok(_)));
+        item
+    }
+}
+
+/// Data is written as a hex string `0x...` and a list as an array
+impl Serialize for RlpItem {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            RlpItem::Data(data) => serializer.serialize_str(&format!("0x{}", hex::encode(data))),
             RlpItem::List(list) => {
-                let mut encoded = list
-                    .into_iter()
-                    .flat_map(Into::<Vec<u8>>::into)
-                    .collect::<Vec<_>>();
-                match encoded.len() {
-                    0..=55 => {
-                        bytes.push(0xC0 + encoded.len() as u8);
-                        bytes.append(&mut encoded);
-                    }
-                    56.. => {
-                        let mut len = encoded
-                            .len()
-                            .to_be_bytes()
-                            .into_iter()
-                            .skip_while(|b| *b == 0x0)
-                            .collect::<Vec<_>>();
-                        bytes.push(0xF7 + len.len() as u8);
-                        bytes.append(&mut len);
-                        bytes.append(&mut encoded);
-                    }
+                let mut seq = serializer.serialize_seq(Some(list.len()))?;
+                for item in list {
+                    seq.serialize_element(item)?;
                 }
+                seq.end()
             }
         }
-        bytes
-    }
-}
-
-impl From<&mut VecDeque<u8>> for RlpItem {
-    fn from(value: &mut VecDeque<u8>) -> Self {
-        let byte = value.pop_front().expect("no more bytes");
-        match byte {
-            0x00..=0x7F => RlpItem::Data(vec![byte]),
-            0x80..=0xBF => {
-                let len = match byte {
-                    0x80..=0xB7 => byte as u64 - 0x80,
-                    0xB8..=0xBF => {
-                        let len = byte - 0xB7;
-                        let len = value.drain(0..len as usize).collect::<Vec<_>>();
-                        len.into_iter().fold(0u64, |a, b| a * 256 + b as u64)
-                    }
-                    _ => unreachable!(),
-                };
-                let item = value.drain(0..len as usize).collect::<Vec<_>>();
-                RlpItem::Data(item)
+    }
+}
+
+/// Also accepts non-negative integers as data, in big endian without leading
+/// zeros, so `0` is the empty string
+impl<'de> Deserialize<'de> for RlpItem {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct RlpVisitor;
+
+        impl<'de> Visitor<'de> for RlpVisitor {
+            type Value = RlpItem;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a hex string, a non-negative integer or a list")
             }
-            0xC0..=0xFF => {
-                let len = match byte {
-                    0xC0..=0xF7 => byte as u64 - 0xC0,
-                    0xF8..=0xFF => {
-                        let len = byte - 0xF7;
-                        let len = value.drain(0..len as usize).collect::<Vec<_>>();
-                        len.into_iter().fold(0u64, |a, b| a * 256 + b as u64)
-                    }
-                    _ => unreachable!(),
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<RlpItem, E> {
+                let hex = v
+                    .strip_prefix("0x")
+                    .ok_or_else(|| E::custom(format!("`{v}` does not start with `0x`")))?;
+                let hex = if hex.len() % 2 == 1 {
+                    format!("0{hex}")
+                } else {
+                    hex.to_string()
                 };
-                let mut items = value.drain(0..len as usize).collect::<VecDeque<_>>();
-                let mut rlp_vals = Vec::new();
-                while !items.is_empty() {
-                    rlp_vals.push(Into::<RlpItem>::into(&mut items));
+                hex::decode(hex).map(RlpItem::Data).map_err(E::custom)
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<RlpItem, E> {
+                Ok(U64::from(v).into())
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<RlpItem, E> {
+                let v = u64::try_from(v).map_err(|_| E::custom("negative integer"))?;
+                self.visit_u64(v)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<RlpItem, A::Error> {
+                let mut list = Vec::new();
+                while let Some(item) = seq.next_element()? {
+                    list.push(item);
                 }
-                RlpItem::List(rlp_vals)
+                Ok(RlpItem::List(list))
             }
         }
+
+        deserializer.deserialize_any(RlpVisitor)
     }
 }
 
@@ -188,13 +803,7 @@ impl fmt::Debug for RlpItem {
                     if data.is_empty() {
                         write!(f, "{:indent$}0x", "", indent = depth)
                     } else {
-                        write!(
-                            f,
-                            "{:indent$}0x{}",
-                            "",
-                            hex::encode(data).trim_start_matches('0'),
-                            indent = depth
-                        )
+                        write!(f, "{:indent$}0x{}", "", hex::encode(data), indent = depth)
                     }
                 }
                 RlpItem::List(list) => match list.len() {
@@ -219,53 +828,241 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_bool() {
         let a: RlpItem = true.into();
         let a: Vec<u8> = a.into();
-        let mut a = VecDeque::<u8>::from(a);
-        let a = Into::<RlpItem>::into(&mut a);
-        let a: bool = a.into();
+        let a = RlpItem::decode(&a).unwrap();
+        let a: bool = a.try_into().unwrap();
         assert_eq!(a, true);
 
         let a: RlpItem = false.into();
         let a: Vec<u8> = a.into();
-        let mut a = VecDeque::<u8>::from(a);
-        let a = Into::<RlpItem>::into(&mut a);
-        let a: bool = a.into();
+        let a = RlpItem::decode(&a).unwrap();
+        let a: bool = a.try_into().unwrap();
         assert_eq!(a, false);
+
+        assert!(bool::try_from(RlpItem::Data(vec![2])).is_err());
+        assert!(bool::try_from(RlpItem::List(vec![])).is_err());
+    }
+
+    #[test]
+    fn test_accessors() {
+        let a = RlpItem::List(vec![RlpItem::Data(vec![1])]);
+        assert_eq!(a.list().unwrap()[0].data().unwrap(), &[1]);
+        assert!(a.data().is_err());
+        assert!(a.list().unwrap()[0].list().is_err());
     }
 
     #[test]
     fn test_u64() {
         let a: RlpItem = U64::from(0u64).into();
         let a: Vec<u8> = a.into();
-        let mut a = VecDeque::<u8>::from(a);
-        let a = Into::<RlpItem>::into(&mut a);
-        let a: U64 = a.into();
+        let a = RlpItem::decode(&a).unwrap();
+        let a: U64 = a.try_into().unwrap();
         assert_eq!(a, U64::from(0u64));
 
         let a: RlpItem = U64::from(123456u64).into();
         let a: Vec<u8> = a.into();
-        let mut a = VecDeque::<u8>::from(a);
-        let a = Into::<RlpItem>::into(&mut a);
-        let a: U64 = a.into();
+        let a = RlpItem::decode(&a).unwrap();
+        let a: U64 = a.try_into().unwrap();
         assert_eq!(a, U64::from(123456u64));
     }
 
+    #[test]
+    fn debug_keeps_leading_zeros() {
+        let a = RlpItem::List(vec![RlpItem::Data(vec![0x0a]), RlpItem::Data(vec![0x00])]);
+        assert_eq!(format!("{a:?}"), "[\n  0x0a\n  0x00\n]");
+    }
+
+    #[test]
+    fn parses_views() {
+        // `[0x0a, "dog"]` with the single byte written as `0x81 0x0a`
+        let bytes = hex::decode("c6810a83646f67").unwrap();
+        let view = RlpView::parse(&bytes, 0).unwrap();
+        assert_eq!(view.len(), 7);
+        let items = view.items().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(items[0].offset, 1);
+        assert_eq!(items[0].header, [0x81]);
+        assert!(!items[0].is_canonical());
+        assert_eq!(items[1].offset, 3);
+        assert_eq!(items[1].payload, b"dog");
+        assert!(items[1].is_canonical());
+        // the payload is borrowed from the input
+        assert_eq!(items[1].payload.as_ptr(), bytes[4..].as_ptr());
+
+        assert!(RlpView::parse(&hex::decode("c68410").unwrap(), 0).is_err());
+        assert!(RlpView::parse(&[], 0).is_err());
+
+        // a malformed item is only found when the list is iterated
+        let view = RlpView::parse(&[0xC3, 0x01, 0x83, 0x01], 0).unwrap();
+        let mut items = view.items();
+        assert!(items.next().unwrap().is_ok());
+        assert!(items.next().unwrap().is_err());
+        assert!(items.next().is_none());
+        assert!(RlpItem::try_from(view).is_err());
+    }
+
+    #[test]
+    fn reads_items() {
+        // `0x02`, `[0x0a, "dog"]` and `0x80` with `1024` bytes of data
+        let mut bytes = hex::decode("02c6810a83646f6780b90400").unwrap();
+        bytes.extend([0xAA; 1024]);
+        let mut reader = RlpReader::new(bytes.as_slice());
+        assert_eq!(reader.next().unwrap().unwrap(), [0x02]);
+        assert_eq!(reader.offset, 1);
+        assert_eq!(reader.next().unwrap().unwrap(), bytes[1..8]);
+        assert_eq!(reader.next().unwrap().unwrap(), [0x80]);
+        assert_eq!(reader.next().unwrap().unwrap().len(), 1027);
+        assert!(reader.next().is_none());
+
+        let mut reader = RlpReader::new([0x01, 0xC3, 0x01].as_slice());
+        assert!(reader.next().unwrap().is_ok());
+        let error = reader.next().unwrap().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the item at offset 1 has 3 bytes, but only 1 remain"
+        );
+        assert!(reader.next().is_none());
+        assert!(RlpReader::new([0xB9, 0x04].as_slice())
+            .next()
+            .unwrap()
+            .is_err());
+    }
+
+    #[test]
+    fn converts_integers_fallibly() {
+        let item = RlpItem::Data([&[1], [0; 8].as_slice()].concat());
+        assert_eq!(U256::try_from(item.clone()).unwrap(), U256::from(1) << 64);
+        let error = U64::try_from(item).unwrap_err();
+        assert_eq!(error.to_string(), "an integer of more than 64 bits");
+        assert!(U256::try_from(RlpItem::Data(vec![1; 33])).is_err());
+        assert!(U64::try_from(RlpItem::List(vec![])).is_err());
+    }
+
+    #[test]
+    fn decodes_strictly() {
+        assert_eq!(
+            U64::decode_bytes(&[0x82, 0x04, 0x00]).unwrap(),
+            U64::from(1024)
+        );
+        assert_eq!(U64::decode_bytes(&[0x80]).unwrap(), U64::ZERO);
+        // leading zeros, a single byte in a header, nine bytes and a list
+        assert!(U64::decode_bytes(&[0x82, 0x00, 0x01]).is_err());
+        assert!(U64::decode_bytes(&[0x81, 0x01]).is_err());
+        assert!(U64::decode_bytes(&hex::decode("89010000000000000000").unwrap()).is_err());
+        assert!(U64::decode_bytes(&[0xC0]).is_err());
+
+        assert!(bool::decode_bytes(&[0x02]).is_err());
+        assert!(Address::decode_bytes(&[0x81, 0xAA]).is_err());
+        assert_eq!(Option::<Address>::decode_bytes(&[0x80]).unwrap(), None);
+        assert!(Vec::<U64>::decode_bytes(&[0xC2, 0x01, 0x00]).is_err());
+        assert!(Vec::<U64>::decode_bytes(&[0xC2, 0x01]).is_err());
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let json = serde_json::json!([1, "0x00", [], ["0xabc", 1024]]);
+        let item: RlpItem = serde_json::from_value(json).unwrap();
+        let bytes: Vec<u8> = item.into();
+        assert_eq!(hex::encode(&bytes), "ca0100c0c6820abc820400");
+
+        let item = RlpItem::decode(&bytes).unwrap();
+        assert_eq!(
+            serde_json::to_value(item).unwrap(),
+            serde_json::json!(["0x01", "0x00", [], ["0x0abc", "0x0400"]])
+        );
+
+        assert!(serde_json::from_value::<RlpItem>(serde_json::json!(-1)).is_err());
+        assert!(serde_json::from_value::<RlpItem>(serde_json::json!("12")).is_err());
+        assert!(RlpItem::decode(&[0x01, 0x02]).is_err());
+    }
+
+    #[test]
+    fn derives_conversions() {
+        use tx_util_derive::{RlpDecodable, RlpEncodable};
+
+        #[derive(Clone, Debug, PartialEq, RlpEncodable, RlpDecodable)]
+        struct Item {
+            a: U64,
+            #[rlp(skip)]
+            b: bool,
+            #[rlp(list)]
+            c: Option<U64>,
+            #[rlp(optional)]
+            d: Option<Address>,
+        }
+
+        let item = Item {
+            a: U64::from(1),
+            b: true,
+            c: None,
+            d: None,
+        };
+        let bytes: Vec<u8> = RlpItem::from(item.clone()).into();
+        assert_eq!(item.encoded(), bytes);
+        assert_eq!(hex::encode(&bytes), "c201c0");
+        let item = Item::decode_bytes(&bytes).unwrap();
+        assert!(!item.b);
+
+        let item = Item {
+            a: U64::from(1),
+            b: false,
+            c: Some(U64::from(2)),
+            d: Some(Address::ZERO),
+        };
+        let bytes: Vec<u8> = RlpItem::from(item.clone()).into();
+        assert_eq!(&hex::encode(&bytes)[..10], "d801c10294");
+        assert_eq!(Item::decode_bytes(&bytes).unwrap(), item);
+        // `c` with two items and a missing `a`
+        assert!(Item::decode_bytes(&hex::decode("c401c20203").unwrap()).is_err());
+        assert!(Item::decode_bytes(&[0xC0]).is_err());
+        assert_eq!(item.encoded(), bytes);
+    }
+
+    #[test]
+    fn encodable_matches_tree() {
+        // single bytes, the 55 and 56 byte boundaries of data and lists, and
+        // a length of two bytes
+        let items = [
+            RlpItem::Data(vec![]),
+            RlpItem::Data(vec![0x7F]),
+            RlpItem::Data(vec![0x80]),
+            RlpItem::Data(vec![0xAA; 55]),
+            RlpItem::Data(vec![0xAA; 56]),
+            RlpItem::Data(vec![0xAA; 1024]),
+            RlpItem::List(vec![RlpItem::Data(vec![0x1]); 55]),
+            RlpItem::List(vec![RlpItem::Data(vec![0x1]); 56]),
+            RlpItem::List(vec![RlpItem::List(vec![]), RlpItem::Data(vec![0xAA; 300])]),
+        ];
+        for item in items {
+            let bytes: Vec<u8> = item.clone().into();
+            assert_eq!(item.length(), bytes.len());
+            assert_eq!(item.encoded(), bytes);
+        }
+
+        for value in [0u64, 1, 0x7F, 0x80, u64::MAX] {
+            let bytes: Vec<u8> = RlpItem::from(U64::from(value)).into();
+            assert_eq!(U64::from(value).encoded(), bytes);
+            let bytes: Vec<u8> = RlpItem::from(U256::from(value)).into();
+            assert_eq!(U256::from(value).encoded(), bytes);
+        }
+        let bytes: Vec<u8> = RlpItem::from(U256::MAX).into();
+        assert_eq!(U256::MAX.encoded(), bytes);
+    }
+
     #[test]
     fn test_u256() {
         let a: RlpItem = U256::from(0u64).into();
         let a: Vec<u8> = a.into();
-        let mut a = VecDeque::<u8>::from(a);
-        let a = Into::<RlpItem>::into(&mut a);
-        let a: U256 = a.into();
+        let a = RlpItem::decode(&a).unwrap();
+        let a: U256 = a.try_into().unwrap();
         assert_eq!(a, U256::from(0u64));
 
         let a: RlpItem = U256::from(123456u64).into();
         let a: Vec<u8> = a.into();
-        let mut a = VecDeque::<u8>::from(a);
-        let a = Into::<RlpItem>::into(&mut a);
-        let a: U256 = a.into();
+        let a = RlpItem::decode(&a).unwrap();
+        let a: U256 = a.try_into().unwrap();
         assert_eq!(a, U256::from(123456u64));
     }
 }
@@ -0,0 +1,282 @@
+use alloy_primitives::{Address, Bytes, B256, U256, U64};
+use color_eyre::eyre::{eyre, Result};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// A minimal blocking JSON-RPC client
+pub(crate) struct Client {
+    url: String,
+    agent: ureq::Agent,
+    id: Cell<u64>,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    result: Option<Value>,
+    error: Option<ResponseError>,
+}
+
+#[derive(Deserialize)]
+struct ResponseError {
+    code: i64,
+    message: String,
+}
+
+/// The fields of a transaction receipt that are reported after sending
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Receipt {
+    pub(crate) block_number: U64,
+    pub(crate) status: U64,
+    pub(crate) gas_used: U256,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FeeHistory {
+    base_fee_per_gas: Vec<U256>,
+}
+
+impl Client {
+    pub(crate) fn new(url: &str) -> Self {
+        Client {
+            url: url.to_string(),
+            agent: ureq::Agent::new(),
+            id: Cell::new(1),
+        }
+    }
+
+    pub(crate) fn request<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        let id = self.id.replace(self.id.get() + 1);
+        let response: Response = self
+            .agent
+            .post(&self.url)
+            .send_json(json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": method,
+                "params": params,
+            }))
+            .map_err(|e| eyre!("`{method}` request to {} failed: {e}", self.url))?
+            .into_json()?;
+        if let Some(error) = response.error {
+            Err(eyre!(
+                "`{method}` failed: {} ({})",
+                error.message,
+                error.code
+            ))?;
+        }
+        serde_json::from_value(response.result.unwrap_or_default())
+            .map_err(|e| eyre!("`{method}` returned an unexpected result: {e}"))
+    }
+
+    pub(crate) fn chain_id(&self) -> Result<U64> {
+        self.request("eth_chainId", json!([]))
+    }
+
+    pub(crate) fn transaction_count(&self, address: Address) -> Result<U64> {
+        self.request("eth_getTransactionCount", json!([address, "pending"]))
+    }
+
+    pub(crate) fn max_priority_fee_per_gas(&self) -> Result<U256> {
+        self.request("eth_maxPriorityFeePerGas", json!([]))
+    }
+
+    /// The base fee of the next block, taken from `eth_feeHistory`
+    pub(crate) fn next_base_fee(&self) -> Result<U256> {
+        let history: FeeHistory = self.request("eth_feeHistory", json!(["0x1", "latest", []]))?;
+        history
+            .base_fee_per_gas
+            .last()
+            .copied()
+            .ok_or_else(|| eyre!("`eth_feeHistory` returned no base fee"))
+    }
+
+    /// Estimates the gas limit of a transaction, see [`transaction_request`]
+    pub(crate) fn estimate_gas<T: Serialize>(
+        &self,
+        tx_type: u8,
+        from: Option<Address>,
+        tx: &T,
+    ) -> Result<U64> {
+        let request = transaction_request(tx_type, from, &serde_json::to_value(tx)?);
+        self.request("eth_estimateGas", json!([request]))
+    }
+
+    pub(crate) fn send_raw_transaction(&self, bytes: &[u8]) -> Result<B256> {
+        self.request(
+            "eth_sendRawTransaction",
+            json!([format!("0x{}", hex::encode(bytes))]),
+        )
+    }
+
+    pub(crate) fn transaction_receipt(&self, hash: B256) -> Result<Option<Receipt>> {
+        self.request("eth_getTransactionReceipt", json!([hash]))
+    }
+
+    pub(crate) fn code(&self, address: Address) -> Result<Bytes> {
+        self.request("eth_getCode", json!([address, "latest"]))
+    }
+
+    /// Polls `eth_getTransactionReceipt` every `interval` until the
+    /// transaction is included or `timeout` has passed
+    pub(crate) fn wait_for_receipt(
+        &self,
+        hash: B256,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<Receipt> {
+        let start = Instant::now();
+        loop {
+            if let Some(receipt) = self.transaction_receipt(hash)? {
+                return Ok(receipt);
+            }
+            if start.elapsed() >= timeout {
+                Err(eyre!("no receipt for {hash} after {}s", timeout.as_secs()))?;
+            }
+            thread::sleep(interval);
+        }
+    }
+}
+
+/// Converts a transaction in the input format to an rpc transaction request.
+///
+/// The gas limit and signature are left out.
+pub(crate) fn transaction_request(tx_type: u8, from: Option<Address>, tx: &Value) -> Value {
+    let mut request = Map::new();
+    let _ = request.insert("type".into(), json!(U64::from(tx_type)));
+    if let Some(from) = from {
+        let _ = request.insert("from".into(), json!(from));
+    }
+    let fields = [
+        ("chainId", "chainId"),
+        ("nonce", "nonce"),
+        ("maxPriorityFeePerGas", "maxPriorityFeePerGas"),
+        ("maxFeePerGas", "maxFeePerGas"),
+        ("destination", "to"),
+        ("amount", "value"),
+        ("data", "input"),
+        ("accessList", "accessList"),
+    ];
+    for (from, to) in fields {
+        if let Some(value) = tx.get(from) {
+            let _ = request.insert(to.into(), value.clone());
+        }
+    }
+    if let Some(list) = tx.get("authorizationList").and_then(Value::as_array) {
+        let list = list
+            .iter()
+            .cloned()
+            .map(|mut auth| {
+                // rpc encodes `yParity` as a quantity
+                if let Some(y_parity) = auth.get_mut("yParity") {
+                    *y_parity = json!(U64::from(y_parity.as_bool().unwrap_or_default()));
+                }
+                auth
+            })
+            .collect();
+        let _ = request.insert("authorizationList".into(), Value::Array(list));
+    }
+    Value::Object(request)
+}
+
+/// Fills fields missing from the transaction input with values from the node.
+///
+/// `sender` is needed to fill the transaction `nonce`, and `authorities` to
+/// fill the `nonce` of each unsigned authorization, matched by index. An
+/// authority's nonce accounts for the sender's own nonce bump and for earlier
+/// authorizations by the same authority.
+///
+/// The gas limit can only be estimated once the authorizations are signed, so
+/// a missing `gasLimit` is set to zero here and `true` is returned so that it
+/// can be filled later with [`Client::estimate_gas`].
+pub(crate) fn fill(
+    client: &Client,
+    input: &mut Value,
+    sender: Option<Address>,
+    authorities: &[Address],
+) -> Result<bool> {
+    let input = input
+        .as_object_mut()
+        .ok_or_else(|| eyre!("the transaction input must be an object"))?;
+
+    if !input.contains_key("chainId") {
+        let _ = input.insert("chainId".into(), json!(client.chain_id()?));
+    }
+    if !input.contains_key("nonce") {
+        let sender = sender.ok_or(eyre!("a `--signer` is required to fill the `nonce`"))?;
+        let _ = input.insert("nonce".into(), json!(client.transaction_count(sender)?));
+    }
+    if !input.contains_key("maxPriorityFeePerGas") {
+        let fee = client.max_priority_fee_per_gas()?;
+        let _ = input.insert("maxPriorityFeePerGas".into(), json!(fee));
+    }
+    if !input.contains_key("maxFeePerGas") {
+        let priority_fee: U256 = serde_json::from_value(input["maxPriorityFeePerGas"].clone())?;
+        let fee = client.next_base_fee()? * U256::from(2) + priority_fee;
+        let _ = input.insert("maxFeePerGas".into(), json!(fee));
+    }
+
+    let tx_nonce: U64 = serde_json::from_value(input["nonce"].clone())?;
+    if let Some(list) = input
+        .get_mut("authorizationList")
+        .and_then(Value::as_array_mut)
+    {
+        let mut next_nonces = HashMap::new();
+        for (i, auth) in list.iter_mut().enumerate() {
+            let Some(auth) = auth.as_object_mut() else {
+                continue;
+            };
+            if auth.contains_key("nonce") {
+                continue;
+            }
+            let authority = *authorities.get(i).ok_or(eyre!(
+                "an `--authorizer` is required to fill the `nonce` of authorization {i}"
+            ))?;
+            let nonce = match next_nonces.get(&authority) {
+                Some(nonce) => *nonce,
+                // the sender's nonce is bumped before authorizations are processed
+                None if Some(authority) == sender => tx_nonce + U64::from(1),
+                None => client.transaction_count(authority)?,
+            };
+            let _ = next_nonces.insert(authority, nonce + U64::from(1));
+            let _ = auth.insert("nonce".into(), json!(nonce));
+        }
+    }
+
+    let estimate_gas = !input.contains_key("gasLimit");
+    if estimate_gas {
+        let _ = input.insert("gasLimit".into(), json!(U64::ZERO));
+    }
+    Ok(estimate_gas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static EIP_7702_SIGNED: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/transactions/eip7702_signed.json"
+    ));
+
+    #[test]
+    fn converts_transaction_request() {
+        let tx: Value = serde_json::from_str(EIP_7702_SIGNED).unwrap();
+        let request = transaction_request(4, Some(Address::ZERO), &tx);
+        assert_eq!(request["type"], json!("0x4"));
+        assert_eq!(request["from"], json!(Address::ZERO));
+        assert_eq!(request["to"], tx["destination"]);
+        assert_eq!(request["value"], tx["amount"]);
+        assert_eq!(request["input"], tx["data"]);
+        assert_eq!(request["authorizationList"][0]["yParity"], json!("0x1"));
+        assert!(request.get("gas").is_none());
+        assert!(request.get("r").is_none());
+    }
+}
@@ -0,0 +1,179 @@
+//! Proptest strategies for the transaction types, weighted towards the values
+//! where encodings change: `0`, `2^64 - 1`, `2^256 - 1`, empty data and
+//! payloads around the 56 byte boundary of a long header
+
+use crate::transaction::{
+    AccessListItem, Authorization, Eip1559, Eip7702, Eip7702Revision, Signature,
+};
+use alloy_primitives::{Address, Bytes, FixedBytes, U256, U64};
+use k256::ecdsa::SigningKey;
+use proptest::{collection::vec, option, prelude::*};
+
+pub(crate) fn u64() -> impl Strategy<Value = U64> {
+    prop_oneof![
+        Just(U64::ZERO),
+        Just(U64::MAX),
+        (0..=0xFFu64).prop_map(U64::from),
+        any::<u64>().prop_map(U64::from),
+    ]
+}
+
+pub(crate) fn u256() -> impl Strategy<Value = U256> {
+    prop_oneof![
+        Just(U256::ZERO),
+        Just(U256::from(u64::MAX)),
+        Just(U256::MAX),
+        (0..=0xFFu64).prop_map(U256::from),
+        any::<u64>().prop_map(U256::from),
+        any::<[u8; 32]>().prop_map(U256::from_be_bytes),
+    ]
+}
+
+/// Data that is empty, a single byte, around 56 bytes or long
+pub(crate) fn bytes() -> impl Strategy<Value = Bytes> {
+    prop_oneof![
+        Just(Bytes::new()),
+        vec(any::<u8>(), 1..=1).prop_map(Bytes::from),
+        vec(any::<u8>(), 54..=57).prop_map(Bytes::from),
+        vec(any::<u8>(), 0..300).prop_map(Bytes::from),
+    ]
+}
+
+pub(crate) fn address() -> impl Strategy<Value = Address> {
+    prop_oneof![
+        Just(Address::ZERO),
+        any::<[u8; 20]>().prop_map(Address::from)
+    ]
+}
+
+pub(crate) fn signature() -> impl Strategy<Value = Signature> {
+    (any::<bool>(), u256(), u256()).prop_map(|(y_parity, r, s)| Signature { y_parity, r, s })
+}
+
+/// A private key, which must be a nonzero scalar below the curve order
+pub(crate) fn signer() -> impl Strategy<Value = Vec<u8>> {
+    any::<[u8; 32]>()
+        .prop_filter("an invalid private key", |key| {
+            SigningKey::from_slice(key).is_ok()
+        })
+        .prop_map(|key| key.to_vec())
+}
+
+pub(crate) fn access_list_item() -> impl Strategy<Value = AccessListItem> {
+    (address(), vec(any::<[u8; 32]>().prop_map(FixedBytes), 0..3)).prop_map(
+        |(address, storage_keys)| AccessListItem {
+            address,
+            storage_keys,
+        },
+    )
+}
+
+/// An authorization in either revision, with a `nonce` in the prague
+/// revision
+pub(crate) fn authorization() -> impl Strategy<Value = Authorization> {
+    (
+        u256(),
+        address(),
+        option::of(u64()),
+        option::of(signature()),
+        prop_oneof![Just(Eip7702Revision::Draft), Just(Eip7702Revision::Prague)],
+    )
+        .prop_map(
+            |(chain_id, address, nonce, signature, revision)| Authorization {
+                chain_id,
+                address,
+                nonce: match revision {
+                    Eip7702Revision::Draft => nonce,
+                    Eip7702Revision::Prague => nonce.or(Some(U64::ZERO)),
+                },
+                signature,
+                revision,
+            },
+        )
+}
+
+pub(crate) fn eip1559() -> impl Strategy<Value = Eip1559> {
+    (
+        (u64(), u64(), u256(), u256(), u64()),
+        option::of(address()),
+        u256(),
+        bytes(),
+        vec(access_list_item(), 0..3),
+        option::of(signature()),
+    )
+        .prop_map(
+            |(
+                (chain_id, nonce, max_priority_fee_per_gas, max_fee_per_gas, gas_limit),
+                destination,
+                amount,
+                data,
+                access_list,
+                signature,
+            )| Eip1559 {
+                chain_id,
+                nonce,
+                max_priority_fee_per_gas,
+                max_fee_per_gas,
+                gas_limit,
+                destination,
+                amount,
+                data,
+                access_list,
+                signature,
+            },
+        )
+}
+
+/// A transaction whose authorizations share one revision, as
+/// [`Eip7702::with_revision`] sets it
+pub(crate) fn eip7702() -> impl Strategy<Value = Eip7702> {
+    (
+        (u64(), u64(), u256(), u256(), u64()),
+        address(),
+        u256(),
+        bytes(),
+        vec(access_list_item(), 0..3),
+        vec(authorization(), 0..3),
+        option::of(signature()),
+    )
+        .prop_map(
+            |(
+                (chain_id, nonce, max_priority_fee_per_gas, max_fee_per_gas, gas_limit),
+                destination,
+                amount,
+                data,
+                access_list,
+                authorization_list,
+                signature,
+            )| {
+                let revision = authorization_list
+                    .first()
+                    .map(|a| a.revision)
+                    .unwrap_or_default();
+                let authorization_list = authorization_list
+                    .into_iter()
+                    .map(|mut auth| {
+                        auth.revision = revision;
+                        auth.nonce = match revision {
+                            Eip7702Revision::Draft => auth.nonce,
+                            Eip7702Revision::Prague => auth.nonce.or(Some(U64::ZERO)),
+                        };
+                        auth
+                    })
+                    .collect();
+                Eip7702 {
+                    chain_id,
+                    nonce,
+                    max_priority_fee_per_gas,
+                    max_fee_per_gas,
+                    gas_limit,
+                    destination,
+                    amount,
+                    data,
+                    access_list,
+                    authorization_list,
+                    signature,
+                }
+            },
+        )
+}
@@ -1,16 +1,39 @@
 #![allow(clippy::vec_init_then_push)]
 
-use crate::rlp::RlpItem;
-use alloy_primitives::{Address, Bytes, FixedBytes, U256, U64};
-use k256::ecdsa::{signature::hazmat::PrehashSigner, SigningKey};
-use serde::{Deserialize, Serialize};
+use crate::{
+    quantity,
+    rlp::{Decodable, Encodable, RlpItem, RlpView},
+};
+use alloy_primitives::{Address, Bytes, FixedBytes, B256, U256, U64};
+use bytes::BufMut;
+use clap::ValueEnum;
+use color_eyre::eyre::{eyre, Result};
+use k256::ecdsa::{signature::hazmat::PrehashSigner, RecoveryId, SigningKey, VerifyingKey};
+use serde::{Deserialize, Deserializer, Serialize};
 use sha3::{Digest, Keccak256};
-use std::vec;
+use std::{iter::zip, vec};
+use tx_util_derive::{RlpDecodable, RlpEncodable};
 
 const EIP1559_TX_TYPE: u8 = 2;
 const EIP7702_TX_TYPE: u8 = 4;
 const AUTHORIZATION_MAGIC: u8 = 5;
 
+/// The order of the secp256k1 curve
+pub(crate) const SECP256K1N: U256 = U256::from_be_slice(&[
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+]);
+
+/// Half the order of the secp256k1 curve, signatures with a larger `s` are
+/// rejected ([EIP-2](https://eips.ethereum.org/EIPS/eip-2))
+pub(crate) const SECP256K1N_HALF: U256 = U256::from_be_slice(&[
+    0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa0,
+]);
+
+/// The prefix of the code written to an authority, followed by the delegate address
+pub(crate) const DELEGATION_PREFIX: [u8; 3] = [0xef, 0x01, 0x00];
+
 /// An [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559) Transaction
 /// ```no_run
 /// 0x02 || rlp([
@@ -28,23 +51,42 @@ const AUTHORIZATION_MAGIC: u8 = 5;
 ///   s
 /// ])
 /// ```
+///
+/// A `null` `destination` creates a contract with `data` as initcode. The key
+/// is required, so that a missing or misspelled one is not a creation.
 #[allow(missing_docs)]
-#[derive(Clone, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, RlpEncodable, RlpDecodable)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct Eip1559 {
-    pub(crate) chain_id: U256,
+    #[serde(deserialize_with = "quantity::deserialize")]
+    pub(crate) chain_id: U64,
+    #[serde(deserialize_with = "quantity::deserialize")]
     pub(crate) nonce: U64,
+    #[serde(deserialize_with = "quantity::deserialize")]
     pub(crate) max_priority_fee_per_gas: U256,
+    #[serde(deserialize_with = "quantity::deserialize")]
     pub(crate) max_fee_per_gas: U256,
-    pub(crate) gas_limit: U256,
-    pub(crate) destination: Address,
+    #[serde(deserialize_with = "quantity::deserialize")]
+    pub(crate) gas_limit: U64,
+    #[serde(deserialize_with = "required")]
+    pub(crate) destination: Option<Address>,
+    #[serde(deserialize_with = "quantity::deserialize")]
     pub(crate) amount: U256,
     pub(crate) data: Bytes,
     pub(crate) access_list: Vec<AccessListItem>,
     #[serde(flatten)]
+    #[rlp(flatten)]
     pub(crate) signature: Option<Signature>,
 }
 
+/// Deserializes a field as usual, but without the implicit `None` of a
+/// missing `Option`
+fn required<'de, D: Deserializer<'de>, T: Deserialize<'de>>(
+    deserializer: D,
+) -> Result<T, D::Error> {
+    T::deserialize(deserializer)
+}
+
 /// An [EIP-7702](https://eips.ethereum.org/EIPS/eip-7702) Transaction
 ///
 /// See [`Authorization`] for `authorization_list`
@@ -65,20 +107,27 @@ pub(crate) struct Eip1559 {
 ///   s
 /// ])
 /// ```
-#[derive(Clone, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, RlpEncodable, RlpDecodable)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct Eip7702 {
-    pub(crate) chain_id: U256,
+    #[serde(deserialize_with = "quantity::deserialize")]
+    pub(crate) chain_id: U64,
+    #[serde(deserialize_with = "quantity::deserialize")]
     pub(crate) nonce: U64,
+    #[serde(deserialize_with = "quantity::deserialize")]
     pub(crate) max_priority_fee_per_gas: U256,
+    #[serde(deserialize_with = "quantity::deserialize")]
     pub(crate) max_fee_per_gas: U256,
-    pub(crate) gas_limit: U256,
+    #[serde(deserialize_with = "quantity::deserialize")]
+    pub(crate) gas_limit: U64,
     pub(crate) destination: Address,
+    #[serde(deserialize_with = "quantity::deserialize")]
     pub(crate) amount: U256,
     pub(crate) data: Bytes,
     pub(crate) access_list: Vec<AccessListItem>,
     pub(crate) authorization_list: Vec<Authorization>,
     #[serde(flatten)]
+    #[rlp(flatten)]
     pub(crate) signature: Option<Signature>,
 }
 
@@ -92,7 +141,7 @@ pub(crate) struct Eip7702 {
 ///   ]
 /// ])
 /// ```
-#[derive(Clone, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, RlpEncodable, RlpDecodable)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct AccessListItem {
     pub(crate) address: Address,
@@ -100,6 +149,8 @@ pub(crate) struct AccessListItem {
 }
 
 /// An [EIP-7702](https://eips.ethereum.org/EIPS/eip-7702) Authorization
+///
+/// The encoding of `nonce` depends on the [`Eip7702Revision`]
 /// ```no_run
 /// rlp([
 ///   chain_id,
@@ -112,130 +163,206 @@ pub(crate) struct AccessListItem {
 ///   s
 /// ])
 /// ```
-#[derive(Clone, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct Authorization {
+    #[serde(deserialize_with = "quantity::deserialize")]
     pub(crate) chain_id: U256,
     pub(crate) address: Address,
+    #[serde(default, deserialize_with = "quantity::option")]
     pub(crate) nonce: Option<U64>,
     #[serde(flatten)]
     pub(crate) signature: Option<Signature>,
+    #[serde(skip)]
+    pub(crate) revision: Eip7702Revision,
+}
+
+/// Revisions of the [EIP-7702](https://eips.ethereum.org/EIPS/eip-7702) Authorization format
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum Eip7702Revision {
+    /// The optional `nonce` is encoded as a list of zero or one items
+    #[default]
+    Draft,
+    /// The `nonce` is required and encoded as an integer,
+    /// `rlp([chain_id, address, nonce, y_parity, r, s])`
+    Prague,
 }
 
 /// A Signature
-#[derive(Clone, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct Signature {
     pub(crate) y_parity: bool,
+    #[serde(deserialize_with = "quantity::deserialize")]
     pub(crate) r: U256,
+    #[serde(deserialize_with = "quantity::deserialize")]
     pub(crate) s: U256,
 }
 
-impl From<Eip1559> for RlpItem {
-    fn from(value: Eip1559) -> Self {
+impl From<Signature> for Vec<RlpItem> {
+    fn from(value: Signature) -> Self {
         let mut items = Vec::new();
-        items.push(value.chain_id.into());
-        items.push(value.nonce.into());
-        items.push(value.max_priority_fee_per_gas.into());
-        items.push(value.max_fee_per_gas.into());
-        items.push(value.gas_limit.into());
-        items.push(value.destination.as_slice().into());
-        items.push(value.amount.into());
-        items.push(value.data.into());
-        items.push(value.access_list.into());
-        if let Some(signature) = value.signature {
-            let mut rlp: Vec<RlpItem> = signature.into();
-            items.append(&mut rlp);
-        }
-        RlpItem::List(items)
+        items.push(value.y_parity.into());
+        items.push(value.r.into());
+        items.push(value.s.into());
+        items
     }
 }
 
-impl From<Eip7702> for RlpItem {
-    fn from(value: Eip7702) -> Self {
-        let mut items = Vec::new();
-        items.push(value.chain_id.into());
-        items.push(value.nonce.into());
-        items.push(value.max_priority_fee_per_gas.into());
-        items.push(value.max_fee_per_gas.into());
-        items.push(value.gas_limit.into());
-        items.push(value.destination.as_slice().into());
-        items.push(value.amount.into());
-        items.push(value.data.into());
-        items.push(value.access_list.into());
-        items.push(value.authorization_list.into());
-        if let Some(signature) = value.signature {
-            let mut rlp: Vec<RlpItem> = signature.into();
-            items.append(&mut rlp);
-        }
-        RlpItem::List(items)
+/// A signature is not an item of its own, its fields are flattened into the
+/// list of a transaction or authorization by `#[rlp(flatten)]`
+impl Signature {
+    /// Writes the items `y_parity, r, s` without a list header
+    fn encode_fields(&self, out: &mut impl BufMut) {
+        self.y_parity.encode(out);
+        self.r.encode(out);
+        self.s.encode(out);
+    }
+
+    /// The length of [`Signature::encode_fields`]
+    fn fields_length(&self) -> usize {
+        self.y_parity.length() + self.r.length() + self.s.length()
     }
 }
 
-impl From<Vec<AccessListItem>> for RlpItem {
-    fn from(value: Vec<AccessListItem>) -> Self {
-        let mut items = Vec::new();
-        for item in value {
-            items.push(RlpItem::List(vec![
-                RlpItem::Data(item.address.as_slice().into()),
-                RlpItem::List(
-                    item.storage_keys
-                        .into_iter()
-                        .map(|k| RlpItem::Data(k.as_slice().into()))
-                        .collect::<Vec<_>>(),
-                ),
-            ]))
+impl TryFrom<&[RlpView<'_>]> for Signature {
+    type Error = color_eyre::Report;
+
+    fn try_from(value: &[RlpView<'_>]) -> Result<Self> {
+        let [y_parity, r, s] = value else {
+            Err(eyre!(
+                "invalid signature, expected 3 items, got {}",
+                value.len()
+            ))?
+        };
+        Ok(Signature {
+            y_parity: bool::decode(*y_parity)?,
+            r: U256::decode(*r)?,
+            s: U256::decode(*s)?,
+        })
+    }
+}
+
+/// An [`Authorization`] in the [`Eip7702Revision::Draft`] encoding
+#[derive(RlpEncodable, RlpDecodable)]
+struct DraftAuthorization {
+    chain_id: U256,
+    address: Address,
+    #[rlp(list)]
+    nonce: Option<U64>,
+    #[rlp(flatten)]
+    signature: Option<Signature>,
+}
+
+/// An [`Authorization`] in the [`Eip7702Revision::Prague`] encoding
+#[derive(RlpEncodable, RlpDecodable)]
+struct PragueAuthorization {
+    chain_id: U256,
+    address: Address,
+    nonce: U64,
+    #[rlp(flatten)]
+    signature: Option<Signature>,
+}
+
+/// A prague authorization without a `nonce`, which [`Eip7702::with_revision`]
+/// rejects, has no prague encoding and is encoded in the draft revision
+impl From<Authorization> for RlpItem {
+    fn from(value: Authorization) -> Self {
+        match PragueAuthorization::try_from(&value) {
+            Ok(auth) if value.revision == Eip7702Revision::Prague => auth.into(),
+            _ => DraftAuthorization::from(&value).into(),
         }
-        RlpItem::List(items)
     }
 }
 
-impl From<Vec<Authorization>> for RlpItem {
-    fn from(value: Vec<Authorization>) -> Self {
-        let mut items = Vec::new();
-        for item in value {
-            items.push(item.into())
+/// Encoded in the draft revision where there is no prague encoding, as in
+/// `From<Authorization> for RlpItem`
+impl Encodable for Authorization {
+    fn encode(&self, out: &mut impl BufMut) {
+        match PragueAuthorization::try_from(self) {
+            Ok(auth) if self.revision == Eip7702Revision::Prague => auth.encode(out),
+            _ => DraftAuthorization::from(self).encode(out),
+        }
+    }
+
+    fn length(&self) -> usize {
+        match PragueAuthorization::try_from(self) {
+            Ok(auth) if self.revision == Eip7702Revision::Prague => auth.length(),
+            _ => DraftAuthorization::from(self).length(),
         }
-        RlpItem::List(items)
     }
 }
 
-impl From<Authorization> for RlpItem {
-    fn from(value: Authorization) -> Self {
-        let mut items = Vec::new();
-        items.push(value.chain_id.into());
-        items.push(value.address.as_slice().into());
-        // EIP-7702 optional nonce is encoded as an empty list
-        items.push(RlpItem::List(
-            value.nonce.map(|n| vec![n.into()]).unwrap_or_default(),
-        ));
-        if let Some(signature) = value.signature {
-            let mut rlp: Vec<RlpItem> = signature.into();
-            items.append(&mut rlp);
+impl Decodable for Authorization {
+    /// The [`Eip7702Revision`] is detected from the encoding of the `nonce`
+    fn decode(view: RlpView<'_>) -> Result<Self> {
+        match view.items().nth(2) {
+            Some(Ok(nonce)) if nonce.is_list => {
+                let auth = DraftAuthorization::decode(view)?;
+                Ok(Authorization {
+                    chain_id: auth.chain_id,
+                    address: auth.address,
+                    nonce: auth.nonce,
+                    signature: auth.signature,
+                    revision: Eip7702Revision::Draft,
+                })
+            }
+            _ => {
+                let auth = PragueAuthorization::decode(view)?;
+                Ok(Authorization {
+                    chain_id: auth.chain_id,
+                    address: auth.address,
+                    nonce: Some(auth.nonce),
+                    signature: auth.signature,
+                    revision: Eip7702Revision::Prague,
+                })
+            }
         }
-        RlpItem::List(items)
     }
 }
 
-impl From<Signature> for Vec<RlpItem> {
-    fn from(value: Signature) -> Self {
-        let mut items = Vec::new();
-        items.push(value.y_parity.into());
-        items.push(value.r.into());
-        items.push(value.s.into());
-        items
+impl From<&Authorization> for DraftAuthorization {
+    fn from(value: &Authorization) -> Self {
+        DraftAuthorization {
+            chain_id: value.chain_id,
+            address: value.address,
+            nonce: value.nonce,
+            signature: value.signature.clone(),
+        }
     }
 }
 
-fn sign_payload(mut payload: Vec<u8>, magic: u8, signer: Vec<u8>) -> Signature {
-    payload.insert(0, magic);
+impl TryFrom<&Authorization> for PragueAuthorization {
+    type Error = color_eyre::Report;
+
+    fn try_from(value: &Authorization) -> Result<Self> {
+        Ok(PragueAuthorization {
+            chain_id: value.chain_id,
+            address: value.address,
+            nonce: value
+                .nonce
+                .ok_or_else(|| eyre!("authorizations require a `nonce` in the prague revision"))?,
+            signature: value.signature.clone(),
+        })
+    }
+}
 
+/// Signs `magic || payload`, where `magic` is the transaction type or the
+/// authorization magic
+pub(crate) fn sign_payload(payload: &[u8], magic: u8, signer: Vec<u8>) -> Signature {
     let mut hasher = Keccak256::new();
-    hasher.update(&payload);
-    let hash = hasher.finalize();
+    hasher.update([magic]);
+    hasher.update(payload);
+    let hash = B256::from_slice(&hasher.finalize());
 
-    let signer = SigningKey::from_slice(&signer).unwrap();
-    let (signature, recovery_id) = signer.sign_prehash(&hash).unwrap();
+    sign_prehash(&hash, &signer)
+}
+
+/// Signs a 32 byte `hash` as is, e.g. a message hash
+pub(crate) fn sign_prehash(hash: &B256, signer: &[u8]) -> Signature {
+    let signer = SigningKey::from_slice(signer).unwrap();
+    let (signature, recovery_id) = signer.sign_prehash(hash.as_slice()).unwrap();
 
     Signature {
         y_parity: recovery_id.is_y_odd(),
@@ -244,45 +371,181 @@ fn sign_payload(mut payload: Vec<u8>, magic: u8, signer: Vec<u8>) -> Signature {
     }
 }
 
+fn recover_payload(payload: &[u8], magic: u8, signature: &Signature) -> Option<Address> {
+    let mut hasher = Keccak256::new();
+    hasher.update([magic]);
+    hasher.update(payload);
+    let hash = B256::from_slice(&hasher.finalize());
+
+    recover_prehash(&hash, signature)
+}
+
+/// Recovers the address that signed `hash`, `None` if the signature is
+/// invalid
+pub(crate) fn recover_prehash(hash: &B256, signature: &Signature) -> Option<Address> {
+    let recovery_id = RecoveryId::new(signature.y_parity, false);
+    let signature = k256::ecdsa::Signature::from_scalars(
+        signature.r.to_be_bytes::<32>(),
+        signature.s.to_be_bytes::<32>(),
+    )
+    .ok()?;
+
+    let key = VerifyingKey::recover_from_prehash(hash.as_slice(), &signature, recovery_id).ok()?;
+    Some(public_key_address(&key))
+}
+
+/// The keccak256 hash of `bytes`, e.g. the hash of an encoded transaction
+pub(crate) fn keccak256(bytes: &[u8]) -> B256 {
+    B256::from_slice(&Keccak256::digest(bytes))
+}
+
+/// The address of the account controlled by the private key `signer`
+pub(crate) fn signer_address(signer: &[u8]) -> Address {
+    let signer = SigningKey::from_slice(signer).unwrap();
+    public_key_address(signer.verifying_key())
+}
+
+fn public_key_address(key: &VerifyingKey) -> Address {
+    let point = key.to_encoded_point(false);
+    let hash = Keccak256::digest(&point.as_bytes()[1..]);
+    Address::from_slice(&hash[12..])
+}
+
+impl Signature {
+    /// The other valid signature for the same message, `(r, n - s, !y_parity)`.
+    ///
+    /// Signatures are produced with a low `s`, so this gives a high `s` which
+    /// is rejected since [EIP-2](https://eips.ethereum.org/EIPS/eip-2).
+    pub(crate) fn malleate(self) -> Self {
+        Signature {
+            y_parity: !self.y_parity,
+            r: self.r,
+            s: SECP256K1N - self.s,
+        }
+    }
+}
+
 impl Authorization {
     pub(crate) fn sign(self, signer: Vec<u8>) -> Self {
         let mut auth = self.clone();
         auth.signature = None;
 
-        let rlp: RlpItem = auth.clone().into();
-
-        auth.signature = Some(sign_payload(rlp.into(), AUTHORIZATION_MAGIC, signer));
+        auth.signature = Some(sign_payload(&auth.encoded(), AUTHORIZATION_MAGIC, signer));
         auth
     }
+
+    /// Recovers the address that signed this authorization, `None` if it is
+    /// unsigned or the signature is invalid
+    pub(crate) fn authority(&self) -> Option<Address> {
+        let mut auth = self.clone();
+        let signature = auth.signature.take()?;
+
+        recover_payload(&auth.encoded(), AUTHORIZATION_MAGIC, &signature)
+    }
+
+    /// The code a node writes to the authority, `0xef0100 || address`.
+    ///
+    /// Delegating to the zero address clears the authority's code instead.
+    pub(crate) fn delegation_code(&self) -> Bytes {
+        if self.address.is_zero() {
+            return Bytes::new();
+        }
+        [DELEGATION_PREFIX.as_slice(), self.address.as_slice()]
+            .concat()
+            .into()
+    }
 }
 
 impl Eip1559 {
+    /// The transaction type followed by the rlp encoding, `0x02 || rlp([..])`
+    pub(crate) fn encode_typed(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(1 + self.length());
+        out.put_u8(EIP1559_TX_TYPE);
+        self.encode(&mut out);
+        out
+    }
+
     pub(crate) fn sign(self, signer: Vec<u8>) -> Self {
         let mut tx = self.clone();
         tx.signature = None;
 
-        let rlp: RlpItem = tx.clone().into();
-
-        tx.signature = Some(sign_payload(rlp.into(), EIP1559_TX_TYPE, signer));
+        tx.signature = Some(sign_payload(&tx.encoded(), EIP1559_TX_TYPE, signer));
         tx
     }
+
+    /// Recovers the address that signed this transaction, `None` if it is
+    /// unsigned or the signature is invalid
+    pub(crate) fn sender(&self) -> Option<Address> {
+        let mut tx = self.clone();
+        let signature = tx.signature.take()?;
+
+        recover_payload(&tx.encoded(), EIP1559_TX_TYPE, &signature)
+    }
 }
 
 impl Eip7702 {
+    /// The transaction type followed by the rlp encoding, `0x04 || rlp([..])`
+    pub(crate) fn encode_typed(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(1 + self.length());
+        out.put_u8(EIP7702_TX_TYPE);
+        self.encode(&mut out);
+        out
+    }
+
+    /// Sets the revision used to encode every item in the `authorization_list`,
+    /// which fails for [`Eip7702Revision::Prague`] if an item has no `nonce`
+    pub(crate) fn with_revision(mut self, revision: Eip7702Revision) -> Result<Self> {
+        for auth in self.authorization_list.iter_mut() {
+            if revision == Eip7702Revision::Prague {
+                let _ = PragueAuthorization::try_from(&*auth)?;
+            }
+            auth.revision = revision;
+        }
+        Ok(self)
+    }
+
+    /// Sets `revision` and signs the `authorization_list` with one of
+    /// `authorizers` per item in order, unless every item is already signed
+    pub(crate) fn authorize(
+        self,
+        revision: Eip7702Revision,
+        authorizers: &[Vec<u8>],
+    ) -> Result<Self> {
+        let mut tx = self.with_revision(revision)?;
+        if tx.authorization_list.iter().any(|a| a.signature.is_none()) {
+            if tx.authorization_list.len() != authorizers.len() {
+                Err(eyre!("the number of `--authorizer` must be equal to the number of items in the `authorization_list`"))?;
+            }
+            tx.authorization_list = zip(tx.authorization_list, authorizers)
+                .map(|(auth, signer)| auth.sign(signer.clone()))
+                .collect();
+        }
+        Ok(tx)
+    }
+
     pub(crate) fn sign(self, signer: Vec<u8>) -> Self {
         let mut tx = self.clone();
         tx.signature = None;
 
-        let rlp: RlpItem = tx.clone().into();
-
-        tx.signature = Some(sign_payload(rlp.into(), EIP7702_TX_TYPE, signer));
+        tx.signature = Some(sign_payload(&tx.encoded(), EIP7702_TX_TYPE, signer));
         tx
     }
+
+    /// Recovers the address that signed this transaction, `None` if it is
+    /// unsigned or the signature is invalid
+    pub(crate) fn sender(&self) -> Option<Address> {
+        let mut tx = self.clone();
+        let signature = tx.signature.take()?;
+
+        recover_payload(&tx.encoded(), EIP7702_TX_TYPE, &signature)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::strategies;
+    use proptest::prelude::*;
 
     static EIP_1559_UNSIGNED: &str = include_str!(concat!(
         env!("CARGO_MANIFEST_DIR"),
@@ -328,6 +591,52 @@ mod tests {
         let _tx: Eip1559 = serde_json::from_str(EIP_1559_HEX_VALS).unwrap();
     }
 
+    #[test]
+    fn deserialize_integers() {
+        let tx: Eip1559 = serde_json::from_str(EIP_1559_UNSIGNED).unwrap();
+        let mut json = serde_json::to_value(&tx).unwrap();
+        json["gasLimit"] = serde_json::json!(tx.gas_limit.to::<u64>());
+        json["chainId"] = serde_json::json!(tx.chain_id.to_string());
+        json["amount"] = serde_json::json!(U256::MAX.to_string());
+        let parsed: Eip1559 = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(parsed.gas_limit, tx.gas_limit);
+        assert_eq!(parsed.chain_id, tx.chain_id);
+        assert_eq!(parsed.amount, U256::MAX);
+
+        json["chainId"] = serde_json::json!("18446744073709551616");
+        let error = serde_json::from_value::<Eip1559>(json.clone()).unwrap_err();
+        assert!(error.to_string().contains("does not fit in 64 bits"));
+        json["chainId"] = serde_json::json!(1);
+        json["nonce"] = serde_json::json!(-1);
+        assert!(serde_json::from_value::<Eip1559>(json).is_err());
+    }
+
+    #[test]
+    fn requires_destination() {
+        let tx: Eip1559 = serde_json::from_str(EIP_1559_UNSIGNED).unwrap();
+        let mut json = serde_json::to_value(&tx).unwrap();
+        json["destination"] = serde_json::Value::Null;
+        let parsed: Eip1559 = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(parsed.destination, None);
+
+        let _ = json.as_object_mut().unwrap().remove("destination");
+        let error = serde_json::from_value::<Eip1559>(json).unwrap_err();
+        assert!(error.to_string().contains("missing field `destination`"));
+    }
+
+    #[test]
+    fn decode_integer_overflow() {
+        let tx: Eip1559 = serde_json::from_str(EIP_1559_SIGNED).unwrap();
+        let RlpItem::List(mut fields) = RlpItem::from(tx) else {
+            unreachable!()
+        };
+        // a chain id of 2^64
+        fields[0] = RlpItem::Data([&[1], [0; 8].as_slice()].concat());
+        let bytes: Vec<u8> = RlpItem::List(fields).into();
+        let error = Eip1559::decode_bytes(&bytes).unwrap_err();
+        assert!(error.to_string().contains("at most 8 are allowed"));
+    }
+
     #[test]
     fn deserialize_eip7702() {
         let _tx: Eip7702 = serde_json::from_str(EIP_7702_UNSIGNED).unwrap();
@@ -338,4 +647,259 @@ mod tests {
         // empty auth
         let _tx: Eip7702 = serde_json::from_str(EIP_7702_EMPTY_AUTH).unwrap();
     }
+
+    #[test]
+    fn encode_authorization_revisions() {
+        let mut tx: Eip7702 = serde_json::from_str(EIP_7702_UNSIGNED).unwrap();
+        let auth = tx.authorization_list[0].clone();
+
+        let draft: RlpItem = auth.clone().into();
+        assert_eq!(
+            draft.list().unwrap()[2].list().unwrap()[0].data().unwrap(),
+            &[2]
+        );
+
+        tx.authorization_list.truncate(1);
+        let tx = tx.with_revision(Eip7702Revision::Prague).unwrap();
+        let prague: RlpItem = tx.authorization_list[0].clone().into();
+        assert_eq!(prague.list().unwrap()[2].data().unwrap(), &[2]);
+    }
+
+    #[test]
+    fn derive_signer_address() {
+        let signer =
+            hex::decode("34954993d403229ee2e01cf6fa8222224935bc47f9534b0c0ea8054764375501")
+                .unwrap();
+        assert_eq!(
+            signer_address(&signer),
+            "0x76dA6B3693eFD723aA7e36d3Ef41aC7663fb1AF8"
+                .parse::<Address>()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn recover_authority() {
+        let signer =
+            hex::decode("34954993d403229ee2e01cf6fa8222224935bc47f9534b0c0ea8054764375501")
+                .unwrap();
+        let mut tx: Eip7702 = serde_json::from_str(EIP_7702_UNSIGNED).unwrap();
+        let auth = tx.authorization_list[0].clone();
+        assert_eq!(auth.authority(), None);
+
+        let auth = auth.sign(signer.clone());
+        assert_eq!(auth.authority(), Some(signer_address(&signer)));
+
+        tx.authorization_list.truncate(1);
+        let tx = tx.with_revision(Eip7702Revision::Prague).unwrap();
+        let auth = tx.authorization_list[0].clone().sign(signer.clone());
+        assert_eq!(auth.authority(), Some(signer_address(&signer)));
+    }
+
+    #[test]
+    fn authorizes() {
+        let signer =
+            hex::decode("34954993d403229ee2e01cf6fa8222224935bc47f9534b0c0ea8054764375501")
+                .unwrap();
+        let mut tx: Eip7702 = serde_json::from_str(EIP_7702_UNSIGNED).unwrap();
+        tx.authorization_list.truncate(1);
+        let authorized = tx
+            .clone()
+            .authorize(Eip7702Revision::Draft, std::slice::from_ref(&signer))
+            .unwrap();
+        assert_eq!(
+            authorized.authorization_list[0].authority(),
+            Some(signer_address(&signer))
+        );
+        // already signed items are kept as they are
+        let again = authorized
+            .clone()
+            .authorize(Eip7702Revision::Draft, &[])
+            .unwrap();
+        assert_eq!(again, authorized);
+
+        let error = tx
+            .clone()
+            .authorize(Eip7702Revision::Draft, &[])
+            .unwrap_err();
+        assert!(error.to_string().contains("the number of `--authorizer`"));
+        tx.authorization_list[0].nonce = None;
+        let error = tx
+            .authorize(Eip7702Revision::Prague, &[signer])
+            .unwrap_err();
+        assert!(error.to_string().contains("require a `nonce`"));
+    }
+
+    #[test]
+    fn decode_rlp() {
+        let tx: Eip1559 = serde_json::from_str(EIP_1559_SIGNED).unwrap();
+        let bytes: Vec<u8> = RlpItem::from(tx.clone()).into();
+        assert_eq!(Eip1559::decode_bytes(&bytes).unwrap().encoded(), bytes);
+        let decoded = Eip1559::decode_bytes(&bytes).unwrap();
+        assert_eq!(
+            serde_json::to_value(decoded).unwrap(),
+            serde_json::to_value(tx).unwrap()
+        );
+
+        for revision in [Eip7702Revision::Draft, Eip7702Revision::Prague] {
+            let mut tx: Eip7702 = serde_json::from_str(EIP_7702_SIGNED).unwrap();
+            tx.authorization_list[0].nonce = Some(U64::from(1));
+            let tx = tx.with_revision(revision).unwrap();
+            let bytes: Vec<u8> = RlpItem::from(tx.clone()).into();
+            let decoded = Eip7702::decode_bytes(&bytes).unwrap();
+            assert_eq!(decoded.encoded(), bytes);
+            assert_eq!(decoded.authorization_list[0].revision, revision);
+            assert_eq!(
+                serde_json::to_value(decoded).unwrap(),
+                serde_json::to_value(tx).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn encodable_matches_tree() {
+        for json in [EIP_1559_UNSIGNED, EIP_1559_SIGNED, EIP_1559_HEX_VALS] {
+            let tx: Eip1559 = serde_json::from_str(json).unwrap();
+            let bytes: Vec<u8> = RlpItem::from(tx.clone()).into();
+            assert_eq!(tx.length(), bytes.len());
+            assert_eq!(tx.encode_typed(), [&[2], bytes.as_slice()].concat());
+        }
+
+        for json in [EIP_7702_UNSIGNED, EIP_7702_SIGNED, EIP_7702_EMPTY_AUTH] {
+            for revision in [Eip7702Revision::Draft, Eip7702Revision::Prague] {
+                let mut tx: Eip7702 = serde_json::from_str(json).unwrap();
+                for auth in tx.authorization_list.iter_mut() {
+                    auth.nonce = auth.nonce.or(Some(U64::from(1)));
+                }
+                let tx = tx.with_revision(revision).unwrap();
+                let bytes: Vec<u8> = RlpItem::from(tx.clone()).into();
+                assert_eq!(tx.length(), bytes.len());
+                assert_eq!(tx.encode_typed(), [&[4], bytes.as_slice()].concat());
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn eip1559_round_trips(tx in strategies::eip1559()) {
+            let json = serde_json::to_value(&tx).unwrap();
+            prop_assert_eq!(&serde_json::from_value::<Eip1559>(json).unwrap(), &tx);
+
+            let bytes = tx.encoded();
+            prop_assert_eq!(&bytes, &Vec::<u8>::from(RlpItem::from(tx.clone())));
+            prop_assert_eq!(&Eip1559::decode_bytes(&bytes).unwrap(), &tx);
+        }
+
+        #[test]
+        fn eip7702_round_trips(tx in strategies::eip7702()) {
+            // the revision is not part of the json
+            let revision = tx.authorization_list.first().map(|a| a.revision).unwrap_or_default();
+            let json = serde_json::to_value(&tx).unwrap();
+            let decoded = serde_json::from_value::<Eip7702>(json).unwrap();
+            prop_assert_eq!(&decoded.with_revision(revision).unwrap(), &tx);
+
+            let bytes = tx.encoded();
+            prop_assert_eq!(&bytes, &Vec::<u8>::from(RlpItem::from(tx.clone())));
+            prop_assert_eq!(&Eip7702::decode_bytes(&bytes).unwrap(), &tx);
+        }
+
+        #[test]
+        fn items_round_trip(
+            item in strategies::access_list_item(),
+            auth in strategies::authorization(),
+            signature in strategies::signature(),
+        ) {
+            let json = serde_json::to_value(&item).unwrap();
+            prop_assert_eq!(&serde_json::from_value::<AccessListItem>(json).unwrap(), &item);
+            prop_assert_eq!(&AccessListItem::decode_bytes(&item.encoded()).unwrap(), &item);
+
+            let json = serde_json::to_value(&auth).unwrap();
+            let mut decoded = serde_json::from_value::<Authorization>(json).unwrap();
+            decoded.revision = auth.revision;
+            prop_assert_eq!(&decoded, &auth);
+            prop_assert_eq!(&Authorization::decode_bytes(&auth.encoded()).unwrap(), &auth);
+
+            let json = serde_json::to_value(&signature).unwrap();
+            prop_assert_eq!(&serde_json::from_value::<Signature>(json).unwrap(), &signature);
+            let bytes = Vec::<u8>::from(RlpItem::List(signature.clone().into()));
+            let items = RlpView::parse_exact(&bytes)
+                .unwrap()
+                .items()
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+            prop_assert_eq!(&Signature::try_from(items.as_slice()).unwrap(), &signature);
+        }
+
+        #[test]
+        fn signs_and_recovers(
+            eip1559 in strategies::eip1559(),
+            eip7702 in strategies::eip7702(),
+            auth in strategies::authorization(),
+            signer in strategies::signer(),
+        ) {
+            let address = signer_address(&signer);
+            prop_assert_eq!(eip1559.sign(signer.clone()).sender(), Some(address));
+            prop_assert_eq!(eip7702.sign(signer.clone()).sender(), Some(address));
+            prop_assert_eq!(auth.sign(signer).authority(), Some(address));
+        }
+    }
+
+    #[test]
+    fn encodes_boundaries() {
+        let header = |tx: &Eip1559, field: usize| {
+            let bytes = tx.encoded();
+            let view = RlpView::parse_exact(&bytes).unwrap();
+            let field = view.items().nth(field).unwrap().unwrap();
+            hex::encode(field.header)
+        };
+        let mut tx = Eip1559 {
+            nonce: U64::MAX,
+            chain_id: U64::MAX,
+            max_fee_per_gas: U256::MAX,
+            ..Default::default()
+        };
+        assert_eq!(header(&tx, 0), "88");
+        assert_eq!(header(&tx, 1), "88");
+        assert_eq!(header(&tx, 2), "80");
+        assert_eq!(header(&tx, 3), "a0");
+        // empty, 55 and 56 bytes of data
+        assert_eq!(header(&tx, 7), "80");
+        tx.data = vec![0xAA; 55].into();
+        assert_eq!(header(&tx, 7), "b7");
+        tx.data = vec![0xAA; 56].into();
+        assert_eq!(header(&tx, 7), "b838");
+        assert_eq!(Eip1559::decode_bytes(&tx.encoded()).unwrap(), tx);
+    }
+
+    #[test]
+    fn delegation_code() {
+        let tx: Eip7702 = serde_json::from_str(EIP_7702_UNSIGNED).unwrap();
+        let mut auth = tx.authorization_list[0].clone();
+        assert_eq!(
+            auth.delegation_code().to_vec(),
+            [DELEGATION_PREFIX.as_slice(), auth.address.as_slice()].concat()
+        );
+
+        auth.address = Address::ZERO;
+        assert!(auth.delegation_code().is_empty());
+    }
+
+    #[test]
+    fn malleate_signature() {
+        let signer =
+            hex::decode("34954993d403229ee2e01cf6fa8222224935bc47f9534b0c0ea8054764375501")
+                .unwrap();
+        let tx: Eip1559 = serde_json::from_str(EIP_1559_UNSIGNED).unwrap();
+        let signature = tx.sign(signer).signature.unwrap();
+        assert!(signature.s <= SECP256K1N_HALF);
+
+        let malleated = signature.clone().malleate();
+        assert!(malleated.s > SECP256K1N_HALF);
+        assert_eq!(malleated.r, signature.r);
+        assert_eq!(malleated.y_parity, !signature.y_parity);
+
+        let restored = malleated.malleate();
+        assert_eq!(restored.s, signature.s);
+        assert_eq!(restored.y_parity, signature.y_parity);
+    }
 }
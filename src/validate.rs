@@ -0,0 +1,234 @@
+use crate::{
+    fork::Fork,
+    gas::MAX_INITCODE_SIZE,
+    transaction::{Eip1559, Eip7702},
+};
+use alloy_primitives::{U256, U64};
+use std::fmt;
+
+/// The largest transaction gas limit accepted from Osaka
+pub(crate) const MAX_TX_GAS_LIMIT: u64 = 1 << 24;
+
+/// A consensus rule broken by a transaction
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Violation {
+    /// The transaction type is not active at the fork
+    TypeNotActive { tx_type: u8, fork: Fork },
+    /// `maxPriorityFeePerGas` is greater than `maxFeePerGas`
+    PriorityFeeAboveMaxFee { priority_fee: U256, max_fee: U256 },
+    /// `gasLimit` does not cover the intrinsic gas or the calldata floor
+    IntrinsicGasTooLow { gas_limit: U64, intrinsic_gas: u64 },
+    /// `gasLimit` is above the cap from Osaka
+    GasLimitAboveCap { gas_limit: U64 },
+    /// `nonce` is 2^64-1 ([EIP-2681](https://eips.ethereum.org/EIPS/eip-2681))
+    NonceMax,
+    /// A type 4 transaction without authorizations
+    EmptyAuthorizationList,
+    /// The initcode of a contract creation is above the limit from Shanghai
+    InitcodeTooLarge { size: usize },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::TypeNotActive { tx_type, fork } => {
+                write!(f, "type {tx_type} transactions are not active in {fork}")
+            }
+            Violation::PriorityFeeAboveMaxFee {
+                priority_fee,
+                max_fee,
+            } => write!(
+                f,
+                "`maxPriorityFeePerGas` {priority_fee} is greater than `maxFeePerGas` {max_fee}"
+            ),
+            Violation::IntrinsicGasTooLow {
+                gas_limit,
+                intrinsic_gas,
+            } => write!(
+                f,
+                "`gasLimit` {gas_limit} is below the intrinsic gas {intrinsic_gas}"
+            ),
+            Violation::GasLimitAboveCap { gas_limit } => write!(
+                f,
+                "`gasLimit` {gas_limit} is above the cap of {MAX_TX_GAS_LIMIT}"
+            ),
+            Violation::NonceMax => write!(f, "`nonce` is 2^64-1"),
+            Violation::EmptyAuthorizationList => write!(f, "`authorizationList` is empty"),
+            Violation::InitcodeTooLarge { size } => write!(
+                f,
+                "initcode of {size} bytes is above the limit of {MAX_INITCODE_SIZE}"
+            ),
+        }
+    }
+}
+
+/// The fields shared by the supported transaction types
+struct Fields<'a> {
+    tx_type: u8,
+    nonce: U64,
+    max_priority_fee_per_gas: U256,
+    max_fee_per_gas: U256,
+    gas_limit: U64,
+    intrinsic_gas: u64,
+    create: bool,
+    data: &'a [u8],
+    authorizations: usize,
+}
+
+impl Fields<'_> {
+    fn validate(&self, fork: Fork) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        let activation = match self.tx_type {
+            0x4 => Fork::Prague,
+            _ => Fork::London,
+        };
+        if fork < activation {
+            violations.push(Violation::TypeNotActive {
+                tx_type: self.tx_type,
+                fork,
+            });
+        }
+        if self.max_priority_fee_per_gas > self.max_fee_per_gas {
+            violations.push(Violation::PriorityFeeAboveMaxFee {
+                priority_fee: self.max_priority_fee_per_gas,
+                max_fee: self.max_fee_per_gas,
+            });
+        }
+        if self.gas_limit < U64::from(self.intrinsic_gas) {
+            violations.push(Violation::IntrinsicGasTooLow {
+                gas_limit: self.gas_limit,
+                intrinsic_gas: self.intrinsic_gas,
+            });
+        }
+        if fork >= Fork::Osaka && self.gas_limit > U64::from(MAX_TX_GAS_LIMIT) {
+            violations.push(Violation::GasLimitAboveCap {
+                gas_limit: self.gas_limit,
+            });
+        }
+        if self.nonce == U64::MAX {
+            violations.push(Violation::NonceMax);
+        }
+        if self.tx_type == 0x4 && self.authorizations == 0 {
+            violations.push(Violation::EmptyAuthorizationList);
+        }
+        if self.create && fork >= Fork::Shanghai && self.data.len() > MAX_INITCODE_SIZE {
+            violations.push(Violation::InitcodeTooLarge {
+                size: self.data.len(),
+            });
+        }
+        violations
+    }
+}
+
+impl Eip1559 {
+    /// Checks the consensus rules of `fork`, returning every violation
+    pub(crate) fn validate(&self, fork: Fork) -> Vec<Violation> {
+        Fields {
+            tx_type: 0x2,
+            nonce: self.nonce,
+            max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+            max_fee_per_gas: self.max_fee_per_gas,
+            gas_limit: self.gas_limit,
+            intrinsic_gas: self.intrinsic_gas(fork).minimum,
+            create: self.destination.is_none(),
+            data: &self.data,
+            authorizations: 0,
+        }
+        .validate(fork)
+    }
+}
+
+impl Eip7702 {
+    /// Checks the consensus rules of `fork`, returning every violation
+    pub(crate) fn validate(&self, fork: Fork) -> Vec<Violation> {
+        Fields {
+            tx_type: 0x4,
+            nonce: self.nonce,
+            max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+            max_fee_per_gas: self.max_fee_per_gas,
+            gas_limit: self.gas_limit,
+            intrinsic_gas: self.intrinsic_gas(fork).minimum,
+            create: false,
+            data: &self.data,
+            authorizations: self.authorization_list.len(),
+        }
+        .validate(fork)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::Bytes;
+
+    static EIP_1559_SIGNED: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/transactions/eip1559_signed.json"
+    ));
+
+    static EIP_7702_EMPTY_AUTH: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/transactions/eip7702_empty_auth.json"
+    ));
+
+    #[test]
+    fn validates_eip1559() {
+        let tx: Eip1559 = serde_json::from_str(EIP_1559_SIGNED).unwrap();
+        assert_eq!(tx.validate(Fork::Osaka), vec![]);
+
+        let mut invalid = tx.clone();
+        invalid.max_priority_fee_per_gas = invalid.max_fee_per_gas + U256::from(1);
+        invalid.gas_limit = U64::from(21000);
+        invalid.nonce = U64::MAX;
+        let violations = invalid.validate(Fork::Osaka);
+        assert_eq!(violations.len(), 3);
+        assert!(matches!(
+            violations[0],
+            Violation::PriorityFeeAboveMaxFee { .. }
+        ));
+        assert_eq!(
+            violations[1],
+            Violation::IntrinsicGasTooLow {
+                gas_limit: U64::from(21000),
+                intrinsic_gas: 21000 + 2400 + 1900 + 29 * 16 + 39 * 4,
+            }
+        );
+        assert_eq!(violations[2], Violation::NonceMax);
+
+        let mut capped = tx.clone();
+        capped.gas_limit = U64::from(MAX_TX_GAS_LIMIT + 1);
+        assert_eq!(capped.validate(Fork::Prague), vec![]);
+        assert_eq!(capped.validate(Fork::Osaka).len(), 1);
+    }
+
+    #[test]
+    fn validates_initcode() {
+        let mut tx: Eip1559 = serde_json::from_str(EIP_1559_SIGNED).unwrap();
+        tx.destination = None;
+        tx.access_list = vec![];
+        tx.data = Bytes::from(vec![1; MAX_INITCODE_SIZE + 1]);
+        tx.gas_limit = U64::from(10_000_000);
+        assert_eq!(tx.validate(Fork::London), vec![]);
+        assert_eq!(
+            tx.validate(Fork::Shanghai),
+            vec![Violation::InitcodeTooLarge {
+                size: MAX_INITCODE_SIZE + 1
+            }]
+        );
+    }
+
+    #[test]
+    fn validates_eip7702() {
+        let tx: Eip7702 = serde_json::from_str(EIP_7702_EMPTY_AUTH).unwrap();
+        assert_eq!(
+            tx.validate(Fork::Cancun),
+            vec![
+                Violation::TypeNotActive {
+                    tx_type: 4,
+                    fork: Fork::Cancun
+                },
+                Violation::EmptyAuthorizationList
+            ]
+        );
+    }
+}
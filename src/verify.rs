@@ -0,0 +1,322 @@
+use crate::{
+    fixture::{self, FixtureFormat, ForkResult},
+    fork::Fork,
+    rlp::{Decodable, Encodable},
+    transaction::{Eip1559, Eip7702},
+};
+use alloy_primitives::{Address, Bytes, B256, U64};
+use clap::ValueEnum;
+use color_eyre::eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+/// A transaction test of ethereum/tests or execution-spec-tests
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct Case {
+    pub(crate) txbytes: Bytes,
+    /// Keyed by fork name, e.g. `Prague`
+    pub(crate) result: BTreeMap<String, Expected>,
+}
+
+/// The expected outcome of a [`Case`] in one fork
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Expected {
+    pub(crate) hash: Option<B256>,
+    pub(crate) sender: Option<Address>,
+    pub(crate) intrinsic_gas: Option<U64>,
+    pub(crate) exception: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum Status {
+    Pass,
+    Fail,
+    /// The transaction type or fork is not supported
+    Skip,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            Status::Pass => "pass",
+            Status::Fail => "FAIL",
+            Status::Skip => "skip",
+        };
+        write!(f, "{status}")
+    }
+}
+
+/// The verdict on one case in one fork
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct Report {
+    pub(crate) file: PathBuf,
+    pub(crate) name: String,
+    pub(crate) fork: String,
+    pub(crate) status: Status,
+    pub(crate) reason: Option<String>,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.status,
+            self.file.display(),
+            self.name,
+            self.fork
+        )?;
+        match &self.reason {
+            Some(reason) => write!(f, ": {reason}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Verifies every case in the json files at `paths`, searching directories
+/// recursively, in file name order
+pub(crate) fn verify_paths(paths: &[PathBuf]) -> Result<Vec<Report>> {
+    let mut files = Vec::new();
+    for path in paths {
+        collect(path, &mut files)?;
+    }
+    let mut reports = Vec::new();
+    for file in files {
+        let json = fs::read_to_string(&file)
+            .map_err(|e| eyre!("unable to read `{}`: {e}", file.display()))?;
+        let cases: BTreeMap<String, Case> = serde_json::from_str(&json)
+            .map_err(|e| eyre!("`{}` is not a transaction test: {e}", file.display()))?;
+        for (name, case) in cases {
+            for (fork, status, reason) in verify(&case) {
+                reports.push(Report {
+                    file: file.clone(),
+                    name: name.clone(),
+                    fork,
+                    status,
+                    reason,
+                });
+            }
+        }
+    }
+    Ok(reports)
+}
+
+fn collect(path: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    if path.is_dir() {
+        let mut entries = fs::read_dir(path)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        entries.sort();
+        for entry in entries {
+            if entry.is_dir() || entry.extension().is_some_and(|ext| ext == "json") {
+                collect(&entry, files)?;
+            }
+        }
+    } else {
+        files.push(path.to_path_buf());
+    }
+    Ok(())
+}
+
+/// Decodes `txbytes`, recovers the sender and recomputes the hash and
+/// intrinsic gas, comparing them to the expected result of each fork.
+///
+/// A transaction that fails to decode, e.g. with trailing bytes or a
+/// non-canonical encoding, is rejected, which passes when any exception is
+/// expected.
+pub(crate) fn verify(case: &Case) -> Vec<(String, Status, Option<String>)> {
+    let decoded = decode(&case.txbytes);
+    case.result
+        .iter()
+        .map(|(name, expected)| {
+            let (status, reason) = match (Fork::from_str(name, true), &decoded) {
+                (Err(_), _) => (Status::Skip, Some("unknown fork".to_string())),
+                (_, Err(Rejection::Unsupported(reason))) => (Status::Skip, Some(reason.clone())),
+                (Ok(fork), Ok(())) => match result(&case.txbytes, fork) {
+                    Ok(actual) => compare(expected, &actual),
+                    Err(e) => reject(expected, &e.to_string()),
+                },
+                (Ok(_), Err(Rejection::Invalid(reason))) => reject(expected, reason),
+            };
+            (name.clone(), status, reason)
+        })
+        .collect()
+}
+
+fn result(txbytes: &Bytes, fork: Fork) -> Result<ForkResult> {
+    fixture::fixture(txbytes, &[fork], FixtureFormat::EthereumTests, None)?
+        .result
+        .remove(&fork.to_string())
+        .ok_or_else(|| eyre!("no result for {fork}"))
+}
+
+fn reject(expected: &Expected, reason: &str) -> (Status, Option<String>) {
+    match &expected.exception {
+        Some(_) => (Status::Pass, Some(reason.to_string())),
+        None => (
+            Status::Fail,
+            Some(format!("expected a valid transaction, {reason}")),
+        ),
+    }
+}
+
+fn compare(expected: &Expected, actual: &ForkResult) -> (Status, Option<String>) {
+    match (&expected.exception, &actual.exception) {
+        (Some(_), Some(exception)) => (Status::Pass, Some(exception.clone())),
+        (Some(expected), None) => (
+            Status::Fail,
+            Some(format!("expected {expected}, but the transaction is valid")),
+        ),
+        (None, Some(exception)) => (
+            Status::Fail,
+            Some(format!("expected a valid transaction, got {exception}")),
+        ),
+        (None, None) => {
+            let sender = actual
+                .sender
+                .as_ref()
+                .and_then(|s| s.parse::<Address>().ok());
+            let mut mismatches = Vec::new();
+            if expected.hash.is_some() && expected.hash != actual.hash {
+                mismatches.push(format!("hash {}", actual.hash.unwrap_or_default()));
+            }
+            if expected.sender.is_some() && expected.sender != sender {
+                mismatches.push(format!("sender {}", sender.unwrap_or_default()));
+            }
+            if expected
+                .intrinsic_gas
+                .is_some_and(|gas| gas != actual.intrinsic_gas)
+            {
+                mismatches.push(format!("intrinsic gas {}", actual.intrinsic_gas));
+            }
+            match mismatches.is_empty() {
+                true => (Status::Pass, None),
+                false => (Status::Fail, Some(format!("got {}", mismatches.join(", ")))),
+            }
+        }
+    }
+}
+
+/// Why `txbytes` could not be decoded
+enum Rejection {
+    /// A transaction type this tool does not decode
+    Unsupported(String),
+    Invalid(String),
+}
+
+/// Checks that `bytes` decode into a supported transaction with a canonical
+/// encoding
+fn decode(bytes: &[u8]) -> Result<(), Rejection> {
+    let (tx_type, payload) = bytes
+        .split_first()
+        .ok_or(Rejection::Invalid("the transaction is empty".to_string()))?;
+    match tx_type {
+        0x2 | 0x4 => {}
+        0xC0.. => Err(Rejection::Unsupported(
+            "legacy transactions are not supported".to_string(),
+        ))?,
+        _ => Err(Rejection::Unsupported(format!(
+            "type {tx_type} transactions are not supported"
+        )))?,
+    }
+    let encoded = match tx_type {
+        0x2 => Eip1559::decode_bytes(payload).map(|tx| tx.encoded()),
+        _ => Eip7702::decode_bytes(payload).map(|tx| tx.encoded()),
+    }
+    .map_err(|e| Rejection::Invalid(format!("invalid rlp, {e}")))?;
+    if encoded != payload {
+        Err(Rejection::Invalid("non-canonical encoding".to_string()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifies_vectors() {
+        let vectors = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/transactions/vectors"));
+        let reports = verify_paths(&[vectors]).unwrap();
+        assert!(!reports.is_empty());
+        let failures = reports
+            .iter()
+            .filter(|r| r.status == Status::Fail)
+            .map(|r| r.to_string())
+            .collect::<Vec<_>>();
+        assert!(failures.is_empty(), "{failures:#?}");
+    }
+
+    /// Runs the upstream suites of a checkout of ethereum/tests at
+    /// `ETHEREUM_TESTS`, which is not vendored
+    #[test]
+    #[ignore = "needs a checkout of ethereum/tests in `ETHEREUM_TESTS`"]
+    fn verifies_ethereum_tests() {
+        let root = PathBuf::from(std::env::var("ETHEREUM_TESTS").unwrap());
+        let suites = ["ttEIP1559", "ttEIP7702", "ttWrongRLP"]
+            .map(|suite| root.join("TransactionTests").join(suite));
+        let reports = verify_paths(&suites).unwrap();
+        assert!(reports.iter().any(|r| r.status == Status::Pass));
+        let failures = reports
+            .iter()
+            .filter(|r| r.status == Status::Fail)
+            .map(|r| r.to_string())
+            .collect::<Vec<_>>();
+        assert!(failures.is_empty(), "{failures:#?}");
+    }
+
+    #[test]
+    fn skips_unsupported() {
+        let case: Case = serde_json::from_value(serde_json::json!({
+            "txbytes": "0xf86c0985",
+            "result": { "Berlin": {}, "London": {} }
+        }))
+        .unwrap();
+        let verdicts = verify(&case);
+        assert_eq!(verdicts[0].1, Status::Skip);
+        assert_eq!(verdicts[0].2.as_deref(), Some("unknown fork"));
+        assert_eq!(
+            verdicts[1].2.as_deref(),
+            Some("legacy transactions are not supported")
+        );
+    }
+
+    #[test]
+    fn fails_mismatches() {
+        let vectors = PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/transactions/vectors/ttEIP1559/validTransfer.json"
+        ));
+        let json = fs::read_to_string(vectors).unwrap();
+        let mut cases: BTreeMap<String, Case> = serde_json::from_str(&json).unwrap();
+        let case = cases.values_mut().next().unwrap();
+        case.result.get_mut("London").unwrap().hash = Some(B256::ZERO);
+        let mut truncated = case.clone();
+        truncated.txbytes = case.txbytes[..case.txbytes.len() - 1].to_vec().into();
+
+        let verdicts = verify(case);
+        assert_eq!(verdicts[0].0, "Cancun");
+        assert_eq!(verdicts[0].1, Status::Pass);
+        let london = verdicts.iter().find(|v| v.0 == "London").unwrap();
+        assert_eq!(london.1, Status::Fail);
+        assert!(london.2.as_ref().unwrap().starts_with("got hash 0x"));
+
+        truncated.result.clear();
+        let _ = truncated.result.insert(
+            "Prague".to_string(),
+            Expected {
+                hash: None,
+                sender: None,
+                intrinsic_gas: None,
+                exception: Some("TR_RLP".to_string()),
+            },
+        );
+        assert_eq!(verify(&truncated)[0].1, Status::Pass);
+    }
+}
@@ -1,4 +1,11 @@
+// the original tests pass `&[..]` to `args`
+#![allow(clippy::needless_borrows_for_generic_args)]
+
 use assert_cmd::Command;
+use predicates::str::contains;
+use serde_json::{json, Value};
+use sha3::{Digest, Keccak256};
+use std::thread;
 
 static EIP_1559_UNSIGNED: &str = include_str!(concat!(
     env!("CARGO_MANIFEST_DIR"),
@@ -83,6 +90,32 @@ fn it_fails_no_singer_1559() {
     assert.code(1);
 }
 
+#[test]
+fn it_fails_invalid_signer() {
+    // zero and above the curve order
+    for signer in ["00".repeat(32), "ff".repeat(32)] {
+        let mut cmd = Command::cargo_bin("tx-util").unwrap();
+        cmd.arg("encode-tx")
+            .args(["--tx-type", "2", "--signer", &signer])
+            .write_stdin(EIP_1559_UNSIGNED)
+            .assert()
+            .failure()
+            .stderr(contains("a supplied `--signer` is invalid"));
+    }
+}
+
+#[test]
+fn it_fails_invalid_message_signer() {
+    for signer in ["00".repeat(32), "ff".repeat(32)] {
+        let mut cmd = Command::cargo_bin("tx-util").unwrap();
+        cmd.arg("sign-message")
+            .args(["--signer", &signer, "hello world"])
+            .assert()
+            .failure()
+            .stderr(contains("a supplied `--signer` is invalid"));
+    }
+}
+
 #[test]
 fn it_encodes_7702() {
     let mut cmd = Command::cargo_bin("tx-util").unwrap();
@@ -100,6 +133,7 @@ fn it_signs_7702_empty_auth() {
     let assert = cmd
         .arg("encode-tx")
         .args(&["--tx-type", "4"])
+        .arg("--no-validate")
         .args(&["--signer", SIGNER])
         .write_stdin(EIP_7702_EMPTY_AUTH)
         .assert();
@@ -112,6 +146,7 @@ fn it_signs_7702_and_auths() {
     let assert = cmd
         .arg("encode-tx")
         .args(&["--tx-type", "4"])
+        .arg("--no-validate")
         .args(&["--signer", SIGNER])
         .args(&["--authorizer", SIGNER])
         .args(&["--authorizer", SIGNER])
@@ -119,3 +154,859 @@ fn it_signs_7702_and_auths() {
         .assert();
     assert.success().stdout("0x04f9015f018084163ef00185081527974c82f6f594695461ef560fa4d3a3e7332c9bfcec261c11a1b68080f838f7948dfdf61f2eb938b207c228b01a2918b196992abfe1a00000000000000000000000000000000000000000000000000000000000000003f8b9f85b0194d571b8bcd11df08f0459009dd1bd664127a431eec10201a0af224f2d45206ef8ed6974fa17337fb148396e2531b14161b04b00d9e63ee34ca03885e8dfcacc288e2519c8be92ad0fb20b78158506fcb0b62829303e48fed13af85a0194d571b8bcd11df08f0459009dd1bd664127a431eec080a050debd048f0d6ab6932a8a7cc5778084fdd8e3d87d51c5b2642942119250ce3ca075c956d12726ff2512ffafe150a06a96fe7664da02d62c0db863c5ff7772135b01a0644c1e935ccdd3a71f6894ab30db8107dad0bbe177c86c447ea2e5900033b3a7a01e01ae276a58089667756d23c9a24c0fdf1d694e3d92de6560222f8dd8b79456");
 }
+
+#[test]
+fn it_signs_1559_toml_file() {
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let assert = cmd
+        .arg("encode-tx")
+        .args(["--tx-type", "2"])
+        .args(["--signer", SIGNER])
+        .args(["--file", "transactions/eip1559_unsigned.toml"])
+        .assert();
+    assert.success().stdout("0x02f8a4010a84163ef00185081527974c82f6f594695461ef560fa4d3a3e7332c9bfcec261c11a1b68080f838f7948dfdf61f2eb938b207c228b01a2918b196992abfe1a0000000000000000000000000000000000000000000000000000000000000000301a0efa0ed9132e900d5dd195698e4a7c14f08dc03c2b3e62b8b9a87b7e08a57c400a00ef4dc89b0c9f4b8e2fdd377e4ed0c3c4c7813a1562c8ada05ebab04925935e7");
+}
+
+#[test]
+fn it_encodes_1559_json5_stdin() {
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let assert = cmd
+        .arg("encode-tx")
+        .args(["--tx-type", "2"])
+        .args(["--input-format", "json5"])
+        .write_stdin(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/transactions/eip1559_signed.json5"
+        )))
+        .assert();
+    assert.success().stdout("0x02f8e9018084163ef00185081527974c82f6f594695461ef560fa4d3a3e7332c9bfcec261c11a1b680b844a9059cbb0000000000000000000000005a96834046c1dff63119eb0eed6330fc5007a1d700000000000000000000000000000000000000000000000000000001a1432720f838f7948dfdf61f2eb938b207c228b01a2918b196992abfe1a0000000000000000000000000000000000000000000000000000000000000000301a052ee022a326abb33e6bebab1fa694043371ab41a7a985ea23d48bd78502be87ca05a0f69dc8009a1e449bfbc8b13220bc40337da1325c261afdac1803f26d0e9d5");
+}
+
+#[test]
+fn it_signs_7702_and_auths_yaml_file() {
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let assert = cmd
+        .arg("encode-tx")
+        .args(["--tx-type", "4"])
+        .arg("--no-validate")
+        .args(["--signer", SIGNER])
+        .args(["--authorizer", SIGNER])
+        .args(["--authorizer", SIGNER])
+        .args(["--file", "transactions/eip7702_unsigned.yaml"])
+        .assert();
+    assert.success().stdout("0x04f9015f018084163ef00185081527974c82f6f594695461ef560fa4d3a3e7332c9bfcec261c11a1b68080f838f7948dfdf61f2eb938b207c228b01a2918b196992abfe1a00000000000000000000000000000000000000000000000000000000000000003f8b9f85b0194d571b8bcd11df08f0459009dd1bd664127a431eec10201a0af224f2d45206ef8ed6974fa17337fb148396e2531b14161b04b00d9e63ee34ca03885e8dfcacc288e2519c8be92ad0fb20b78158506fcb0b62829303e48fed13af85a0194d571b8bcd11df08f0459009dd1bd664127a431eec080a050debd048f0d6ab6932a8a7cc5778084fdd8e3d87d51c5b2642942119250ce3ca075c956d12726ff2512ffafe150a06a96fe7664da02d62c0db863c5ff7772135b01a0644c1e935ccdd3a71f6894ab30db8107dad0bbe177c86c447ea2e5900033b3a7a01e01ae276a58089667756d23c9a24c0fdf1d694e3d92de6560222f8dd8b79456");
+}
+
+#[test]
+fn it_overrides_fields() {
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let assert = cmd
+        .arg("encode-tx")
+        .args(["--tx-type", "2"])
+        .args(["--signer", SIGNER])
+        .args(["--set", "nonce=5"])
+        .args([
+            "--set",
+            "accessList[0].storageKeys[0]=0x0000000000000000000000000000000000000000000000000000000000000004",
+        ])
+        .write_stdin(EIP_1559_UNSIGNED)
+        .assert();
+    assert.success().stdout("0x02f8a4010584163ef00185081527974c82f6f594695461ef560fa4d3a3e7332c9bfcec261c11a1b68080f838f7948dfdf61f2eb938b207c228b01a2918b196992abfe1a0000000000000000000000000000000000000000000000000000000000000000480a0c8b6e084bf4759d06dc210b974fb38179be1697ac314766f2fbcae26f4911bdaa0276872fc2f30774bbbf71752acf280b37038873ed26d33e6662f9146f1f61a58");
+}
+
+#[test]
+fn it_fails_unknown_override() {
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let assert = cmd
+        .arg("encode-tx")
+        .args(["--tx-type", "4"])
+        .args(["--set", "authorizationList[3].chainId=0"])
+        .write_stdin(EIP_7702_SIGNED)
+        .assert();
+    assert.code(1);
+}
+
+#[test]
+fn it_signs_1559_with_profile() {
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let assert = cmd
+        .arg("encode-tx")
+        .args(["--tx-type", "2"])
+        .args(["--profile", "test"])
+        .args(["--config", "transactions/tx-util.toml"])
+        .args(["--file", "transactions/eip1559_partial.json"])
+        .assert();
+    assert.success().stdout("0x02f8a4010a84163ef00185081527974c82f6f594695461ef560fa4d3a3e7332c9bfcec261c11a1b68080f838f7948dfdf61f2eb938b207c228b01a2918b196992abfe1a0000000000000000000000000000000000000000000000000000000000000000301a0efa0ed9132e900d5dd195698e4a7c14f08dc03c2b3e62b8b9a87b7e08a57c400a00ef4dc89b0c9f4b8e2fdd377e4ed0c3c4c7813a1562c8ada05ebab04925935e7");
+}
+
+#[test]
+fn it_resolves_profile_signer_when_signing() {
+    // a signed transaction needs no key
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    cmd.arg("encode-tx")
+        .args(["--tx-type", "2"])
+        .args(["--profile", "unset-signer"])
+        .args(["--config", "transactions/tx-util.toml"])
+        .write_stdin(EIP_1559_SIGNED)
+        .assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    cmd.arg("encode-tx")
+        .args(["--tx-type", "2"])
+        .args(["--profile", "unset-signer"])
+        .args(["--config", "transactions/tx-util.toml"])
+        .write_stdin(EIP_1559_UNSIGNED)
+        .assert()
+        .failure()
+        .stderr(contains(
+            "the signer variable `TX_UTIL_TEST_UNSET_KEY` is not set",
+        ));
+}
+
+#[test]
+fn it_fails_prague_auth_without_nonce() {
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let assert = cmd
+        .arg("encode-tx")
+        .args(["--tx-type", "4"])
+        .args(["--profile", "prague"])
+        .args(["--config", "transactions/tx-util.toml"])
+        .args(["--signer", SIGNER])
+        .args(["--authorizer", SIGNER])
+        .args(["--authorizer", SIGNER])
+        .write_stdin(EIP_7702_UNSIGNED)
+        .assert();
+    assert.code(1);
+}
+
+/// Serves JSON-RPC requests on a local port, answering each with `respond`
+fn mock_rpc(respond: fn(&str, &Value) -> Value) -> String {
+    let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+    let _ = thread::spawn(move || {
+        for mut request in server.incoming_requests() {
+            let mut body = String::new();
+            let _ = request.as_reader().read_to_string(&mut body).unwrap();
+            let body: Value = serde_json::from_str(&body).unwrap();
+            let result = respond(body["method"].as_str().unwrap(), &body["params"]);
+            let response = json!({ "jsonrpc": "2.0", "id": body["id"], "result": result });
+            let header = "Content-Type: application/json"
+                .parse::<tiny_http::Header>()
+                .unwrap();
+            request
+                .respond(tiny_http::Response::from_string(response.to_string()).with_header(header))
+                .unwrap();
+        }
+    });
+    url
+}
+
+fn devnet(method: &str, _params: &Value) -> Value {
+    match method {
+        "eth_chainId" => json!("0x539"),
+        "eth_getTransactionCount" => json!("0x7"),
+        "eth_maxPriorityFeePerGas" => json!("0x3b9aca00"),
+        "eth_feeHistory" => json!({ "oldestBlock": "0x1", "baseFeePerGas": ["0x7", "0x8"] }),
+        "eth_estimateGas" => json!("0x5208"),
+        _ => Value::Null,
+    }
+}
+
+fn encode(tx_type: &str, input: &str, args: &[&str]) -> String {
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let assert = cmd
+        .arg("encode-tx")
+        .args(["--tx-type", tx_type])
+        .args(["--signer", SIGNER])
+        .args(args)
+        .write_stdin(input)
+        .assert()
+        .success();
+    String::from_utf8(assert.get_output().stdout.clone()).unwrap()
+}
+
+#[test]
+fn it_fills_1559_from_rpc() {
+    let url = mock_rpc(devnet);
+    let filled = encode(
+        "2",
+        r#"{
+            "destination": "0x695461EF560Fa4d3a3e7332c9bfcEC261c11a1B6",
+            "amount": 0,
+            "data": "0x",
+            "accessList": []
+        }"#,
+        &["--rpc-url", &url, "--fill"],
+    );
+    let expected = encode(
+        "2",
+        r#"{
+            "chainId": 1337,
+            "nonce": 7,
+            "maxPriorityFeePerGas": 1000000000,
+            "maxFeePerGas": 1000000016,
+            "gasLimit": 21000,
+            "destination": "0x695461EF560Fa4d3a3e7332c9bfcEC261c11a1B6",
+            "amount": 0,
+            "data": "0x",
+            "accessList": []
+        }"#,
+        &[],
+    );
+    assert_eq!(filled, expected);
+}
+
+#[test]
+fn it_fills_7702_auth_nonces_from_rpc() {
+    let url = mock_rpc(devnet);
+    let filled = encode(
+        "4",
+        r#"{
+            "chainId": 1,
+            "destination": "0x695461EF560Fa4d3a3e7332c9bfcEC261c11a1B6",
+            "amount": 0,
+            "data": "0x",
+            "accessList": [],
+            "authorizationList": [
+                { "chainId": 1, "address": "0xD571b8bcd11dF08F0459009Dd1bd664127A431Ee" },
+                { "chainId": 1, "address": "0xD571b8bcd11dF08F0459009Dd1bd664127A431Ee" }
+            ]
+        }"#,
+        &[
+            "--rpc-url",
+            &url,
+            "--fill",
+            "--eip7702-revision",
+            "prague",
+            "--no-validate",
+            "--authorizer",
+            SIGNER,
+            "--authorizer",
+            SIGNER,
+        ],
+    );
+    let expected = encode(
+        "4",
+        r#"{
+            "chainId": 1,
+            "nonce": 7,
+            "maxPriorityFeePerGas": 1000000000,
+            "maxFeePerGas": 1000000016,
+            "gasLimit": 21000,
+            "destination": "0x695461EF560Fa4d3a3e7332c9bfcEC261c11a1B6",
+            "amount": 0,
+            "data": "0x",
+            "accessList": [],
+            "authorizationList": [
+                { "chainId": 1, "address": "0xD571b8bcd11dF08F0459009Dd1bd664127A431Ee", "nonce": 8 },
+                { "chainId": 1, "address": "0xD571b8bcd11dF08F0459009Dd1bd664127A431Ee", "nonce": 9 }
+            ]
+        }"#,
+        &[
+            "--eip7702-revision",
+            "prague",
+            "--no-validate",
+            "--authorizer",
+            SIGNER,
+            "--authorizer",
+            SIGNER,
+        ],
+    );
+    assert_eq!(filled, expected);
+}
+
+#[test]
+fn it_fails_fill_without_rpc() {
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let assert = cmd
+        .arg("encode-tx")
+        .args(["--tx-type", "2"])
+        .args(["--signer", SIGNER])
+        .arg("--fill")
+        .write_stdin(EIP_1559_UNSIGNED)
+        .assert();
+    assert.code(1);
+}
+
+fn devnet_send(method: &str, params: &Value) -> Value {
+    match method {
+        "eth_sendRawTransaction" => {
+            let bytes = hex::decode(&params[0].as_str().unwrap()[2..]).unwrap();
+            json!(format!("0x{}", hex::encode(Keccak256::digest(bytes))))
+        }
+        "eth_getTransactionReceipt" => json!({
+            "transactionHash": params[0],
+            "blockNumber": "0x10",
+            "status": "0x1",
+            "gasUsed": "0xb3b0"
+        }),
+        "eth_getCode" => json!("0xef0100d571b8bcd11df08f0459009dd1bd664127a431ee"),
+        _ => devnet(method, params),
+    }
+}
+
+#[test]
+fn it_sends_7702_and_waits() {
+    let url = mock_rpc(devnet_send);
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let assert = cmd
+        .arg("encode-tx")
+        .args(["--tx-type", "4"])
+        .arg("--no-validate")
+        .args(["--signer", SIGNER])
+        .args(["--authorizer", SIGNER])
+        .args(["--authorizer", SIGNER])
+        .args(["--rpc-url", &url])
+        .args(["--send", "--wait"])
+        .write_stdin(EIP_7702_UNSIGNED)
+        .assert();
+    assert
+        .success()
+        .stdout("0x04f9015f018084163ef00185081527974c82f6f594695461ef560fa4d3a3e7332c9bfcec261c11a1b68080f838f7948dfdf61f2eb938b207c228b01a2918b196992abfe1a00000000000000000000000000000000000000000000000000000000000000003f8b9f85b0194d571b8bcd11df08f0459009dd1bd664127a431eec10201a0af224f2d45206ef8ed6974fa17337fb148396e2531b14161b04b00d9e63ee34ca03885e8dfcacc288e2519c8be92ad0fb20b78158506fcb0b62829303e48fed13af85a0194d571b8bcd11df08f0459009dd1bd664127a431eec080a050debd048f0d6ab6932a8a7cc5778084fdd8e3d87d51c5b2642942119250ce3ca075c956d12726ff2512ffafe150a06a96fe7664da02d62c0db863c5ff7772135b01a0644c1e935ccdd3a71f6894ab30db8107dad0bbe177c86c447ea2e5900033b3a7a01e01ae276a58089667756d23c9a24c0fdf1d694e3d92de6560222f8dd8b79456")
+        .stderr(contains("status: success"))
+        .stderr(contains("gas used: 46000"))
+        .stderr(contains(
+            "authority 1 0x76dA6B3693eFD723aA7e36d3Ef41aC7663fb1AF8: 0xef0100d571b8bcd11df08f0459009dd1bd664127a431ee",
+        ));
+}
+
+#[test]
+fn it_fails_send_hash_mismatch() {
+    let url = mock_rpc(|method, params| match method {
+        "eth_sendRawTransaction" => json!(format!("0x{}", "00".repeat(32))),
+        _ => devnet(method, params),
+    });
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let assert = cmd
+        .arg("encode-tx")
+        .args(["--tx-type", "2"])
+        .args(["--rpc-url", &url])
+        .arg("--send")
+        .write_stdin(EIP_1559_SIGNED)
+        .assert();
+    assert.code(1);
+}
+
+#[test]
+fn it_encodes_1559_call() {
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let assert = cmd
+        .arg("encode-tx")
+        .args(["--tx-type", "2"])
+        .args(["--file", "transactions/eip1559_call.json"])
+        .assert();
+    assert.success().stdout("0x02f8e9018084163ef00185081527974c82f6f594695461ef560fa4d3a3e7332c9bfcec261c11a1b680b844a9059cbb0000000000000000000000005a96834046c1dff63119eb0eed6330fc5007a1d700000000000000000000000000000000000000000000000000000001a1432720f838f7948dfdf61f2eb938b207c228b01a2918b196992abfe1a0000000000000000000000000000000000000000000000000000000000000000301a052ee022a326abb33e6bebab1fa694043371ab41a7a985ea23d48bd78502be87ca05a0f69dc8009a1e449bfbc8b13220bc40337da1325c261afdac1803f26d0e9d5");
+}
+
+#[test]
+fn it_encodes_1559_calldata_args() {
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let assert = cmd
+        .arg("encode-tx")
+        .args(["--tx-type", "2"])
+        .args(["--calldata-sig", "transfer(address,uint256)"])
+        .args(["--arg", "0x5a96834046c1dff63119eb0eed6330fc5007a1d7"])
+        .args(["--arg", "7000500000"])
+        .write_stdin(EIP_1559_SIGNED)
+        .assert();
+    assert.success().stdout("0x02f8e9018084163ef00185081527974c82f6f594695461ef560fa4d3a3e7332c9bfcec261c11a1b680b844a9059cbb0000000000000000000000005a96834046c1dff63119eb0eed6330fc5007a1d700000000000000000000000000000000000000000000000000000001a1432720f838f7948dfdf61f2eb938b207c228b01a2918b196992abfe1a0000000000000000000000000000000000000000000000000000000000000000301a052ee022a326abb33e6bebab1fa694043371ab41a7a985ea23d48bd78502be87ca05a0f69dc8009a1e449bfbc8b13220bc40337da1325c261afdac1803f26d0e9d5");
+}
+
+static EIP_1559_CALL_HEX: &str = "0x02f8e9018084163ef00185081527974c82f6f594695461ef560fa4d3a3e7332c9bfcec261c11a1b680b844a9059cbb0000000000000000000000005a96834046c1dff63119eb0eed6330fc5007a1d700000000000000000000000000000000000000000000000000000001a1432720f838f7948dfdf61f2eb938b207c228b01a2918b196992abfe1a0000000000000000000000000000000000000000000000000000000000000000301a052ee022a326abb33e6bebab1fa694043371ab41a7a985ea23d48bd78502be87ca05a0f69dc8009a1e449bfbc8b13220bc40337da1325c261afdac1803f26d0e9d5";
+
+#[test]
+fn it_decodes_1559_with_abi() {
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let output = cmd
+        .arg("decode-tx")
+        .args(["--abi", "transactions/erc20.abi.json"])
+        .write_stdin(EIP_1559_CALL_HEX)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let tx: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(tx["type"], json!(2));
+    assert_eq!(tx["gasLimit"], json!("0xf6f5"));
+    assert_eq!(
+        tx["decodedData"],
+        json!({
+            "function": "transfer",
+            "signature": "transfer(address,uint256)",
+            "args": ["0x5a96834046c1DFf63119eB0eed6330fc5007a1d7", 7000500000u64],
+        })
+    );
+}
+
+#[test]
+fn it_decodes_data_when_streaming_and_inspecting() {
+    let tx = hex::decode(&EIP_1559_CALL_HEX[2..]).unwrap();
+    let stream = std::env::temp_dir().join("tx-util-stream-abi.bin");
+    std::fs::write(&stream, &tx).unwrap();
+
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let output = cmd
+        .arg("decode-tx")
+        .arg("--stream")
+        .arg(&stream)
+        .args(["--signature", "transfer(address,uint256)"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let decoded: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(decoded["decodedData"]["function"], json!("transfer"));
+
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    cmd.args(["rlp", "inspect", EIP_1559_CALL_HEX])
+        .args(["--abi", "transactions/erc20.abi.json"])
+        .assert()
+        .success()
+        .stdout(contains("    data: 0xa9059cbb"))
+        .stdout(contains(r#"decodedData: {"args":["0x5a96834046c1DFf63119eB0eed6330fc5007a1d7",7000500000],"function":"transfer""#));
+}
+
+#[test]
+fn it_streams_transactions() {
+    let tx = hex::decode(&EIP_1559_CALL_HEX[2..]).unwrap();
+    let stream = std::env::temp_dir().join("tx-util-stream.bin");
+    std::fs::write(&stream, [tx.as_slice(), tx.as_slice()].concat()).unwrap();
+
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let output = cmd
+        .arg("decode-tx")
+        .arg("--stream")
+        .arg(&stream)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let lines = String::from_utf8(output.stdout).unwrap();
+    let lines = lines.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+    let decoded: Value = serde_json::from_str(lines[1]).unwrap();
+    assert_eq!(decoded["gasLimit"], json!("0xf6f5"));
+
+    std::fs::write(&stream, [tx, vec![0x02, 0xC3, 0x01]].concat()).unwrap();
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    cmd.arg("decode-tx")
+        .arg("--stream")
+        .arg(&stream)
+        .assert()
+        .failure()
+        .stdout(contains("\"type\":2"))
+        .stderr(contains(
+            "the item at offset 237 has 3 bytes, but only 1 remain",
+        ));
+}
+
+#[test]
+fn it_decodes_unknown_selector() {
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let output = cmd
+        .arg("decode-tx")
+        .arg(EIP_1559_CALL_HEX)
+        .args(["--signature", "approve(address,uint256)"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let tx: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        tx["decodedData"],
+        json!({ "selector": "0xa9059cbb", "error": "unknown selector" })
+    );
+}
+
+#[test]
+fn it_simulates_7702_auths() {
+    let encoded = encode(
+        "4",
+        EIP_7702_UNSIGNED,
+        &[
+            "--no-validate",
+            "--authorizer",
+            SIGNER,
+            "--authorizer",
+            SIGNER,
+        ],
+    );
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let output = cmd
+        .arg("simulate-auth")
+        .write_stdin(encoded)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let outcomes: Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    // the signer is also the sender, whose nonce is bumped from 0 to 1 first
+    assert_eq!(
+        outcomes[0]["skipped"],
+        json!("nonce 2 does not match the authority nonce 1")
+    );
+    assert_eq!(
+        outcomes[1]["code"],
+        json!("0xef0100d571b8bcd11df08f0459009dd1bd664127a431ee")
+    );
+    assert_eq!(outcomes[1]["clearsDelegation"], json!(false));
+    assert_eq!(outcomes[1]["nextNonce"], json!("0x2"));
+    assert_eq!(outcomes[1]["skipped"], Value::Null);
+}
+
+#[test]
+fn it_simulates_7702_auths_with_rpc_code() {
+    let url = mock_rpc(|method, params| match method {
+        "eth_getCode" => json!("0x6000"),
+        _ => devnet(method, params),
+    });
+    let encoded = encode(
+        "4",
+        EIP_7702_UNSIGNED,
+        &[
+            "--no-validate",
+            "--authorizer",
+            SIGNER,
+            "--authorizer",
+            SIGNER,
+        ],
+    );
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let output = cmd
+        .arg("simulate-auth")
+        .arg(encoded)
+        .args(["--rpc-url", &url])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let outcomes: Value = serde_json::from_slice(&output.stdout).unwrap();
+    for outcome in outcomes.as_array().unwrap() {
+        assert_eq!(
+            outcome["skipped"],
+            json!("the authority has code that is not a delegation")
+        );
+    }
+}
+
+#[test]
+fn it_fails_validation_with_all_violations() {
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let assert = cmd
+        .arg("encode-tx")
+        .args(["--tx-type", "4"])
+        .args(["--signer", SIGNER])
+        .args(["--set", "maxPriorityFeePerGas=34714654541"])
+        .write_stdin(EIP_7702_EMPTY_AUTH)
+        .assert();
+    assert
+        .code(1)
+        .stderr(contains("invalid in Osaka"))
+        .stderr(contains("`maxPriorityFeePerGas` 34714654541 is greater"))
+        .stderr(contains("`authorizationList` is empty"));
+}
+
+#[test]
+fn it_validates_against_fork() {
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let assert = cmd
+        .arg("encode-tx")
+        .args(["--tx-type", "4"])
+        .args(["--fork", "cancun"])
+        .write_stdin(EIP_7702_SIGNED)
+        .assert();
+    assert
+        .code(1)
+        .stderr(contains("type 4 transactions are not active in Cancun"));
+}
+
+#[test]
+fn it_computes_intrinsic_gas() {
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let output = cmd
+        .arg("intrinsic-gas")
+        .args(["--tx-type", "4"])
+        .args(["--fork", "prague"])
+        .write_stdin(r#"{ "data": "0x00ff", "authorizationList": [{}] }"#)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let gas: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(gas["calldata"], json!(4 + 16));
+    assert_eq!(gas["authorizations"], json!(25000));
+    assert_eq!(gas["floor"], json!(21000 + 50));
+    assert_eq!(gas["minimum"], json!(21000 + 20 + 25000));
+}
+
+#[test]
+fn it_mutates_1559() {
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let output = cmd
+        .arg("mutate")
+        .arg(EIP_1559_CALL_HEX)
+        .args(["--signer", SIGNER])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let mutants: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let mutants = mutants.as_array().unwrap();
+    let names = mutants
+        .iter()
+        .map(|m| m["name"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    for name in [
+        "wrong-type",
+        "leading-zero-nonce",
+        "y-parity-2",
+        "high-s",
+        "r-zero",
+    ] {
+        assert!(names.contains(&name), "missing {name}");
+    }
+    assert!(!names.contains(&"empty-authorization-list"));
+    assert!(mutants.iter().all(|m| m["reason"].is_string()));
+
+    let wrong_type = mutants.iter().find(|m| m["name"] == "wrong-type").unwrap();
+    assert!(wrong_type["tx"].as_str().unwrap().starts_with("0x04f8e9"));
+}
+
+#[test]
+fn it_encodes_high_s_authorization() {
+    let encoded = encode(
+        "4",
+        EIP_7702_UNSIGNED,
+        &[
+            "--no-validate",
+            "--authorizer",
+            SIGNER,
+            "--authorizer",
+            SIGNER,
+            "--high-s-auth",
+            "1",
+        ],
+    );
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let output = cmd
+        .arg("simulate-auth")
+        .write_stdin(encoded)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let outcomes: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(outcomes[0]["skipped"].as_str().unwrap().contains("nonce"));
+    assert!(outcomes[1]["skipped"]
+        .as_str()
+        .unwrap()
+        .contains("upper half"));
+}
+
+#[test]
+fn it_malleates_7702() {
+    let high_s = "0xa5f096237ff65e1bb6404374ecddf43ab77702d389863e8be510de4da965576c";
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let output = cmd
+        .arg("malleate")
+        .args([
+            "--tx-type",
+            "4",
+            "--file",
+            "transactions/eip7702_signed.json",
+        ])
+        .args(["--tx", "--auth", "0"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let tx: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(tx["s"], json!(high_s));
+    assert_eq!(tx["yParity"], json!(false));
+    assert_eq!(tx["authorizationList"][0]["s"], json!(high_s));
+    assert_eq!(tx["authorizationList"][0]["yParity"], json!(false));
+
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    cmd.arg("malleate")
+        .args([
+            "--tx-type",
+            "4",
+            "--file",
+            "transactions/eip7702_signed.json",
+        ])
+        .args(["--auth", "1"])
+        .assert()
+        .failure()
+        .stderr(contains("there is no authorization 1"));
+}
+
+#[test]
+fn it_fails_unknown_tx_type() {
+    for args in [vec!["encode-tx"], vec!["malleate", "--tx"]] {
+        let mut cmd = Command::cargo_bin("tx-util").unwrap();
+        cmd.args(args)
+            .args(["--tx-type", "3"])
+            .write_stdin(EIP_1559_SIGNED)
+            .assert()
+            .failure()
+            .stderr(contains("invalid transaction type `3`"));
+    }
+}
+
+#[test]
+fn it_exports_fixtures() {
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let output = cmd
+        .arg("export-fixtures")
+        .args(["transactions/eip1559_unsigned.json", "--tx-type", "2"])
+        .args(["--signer", SIGNER])
+        .args(["--fork", "london", "--fork", "osaka"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let fixtures: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let fixture = &fixtures["eip1559_unsigned"];
+
+    let txbytes = fixture["txbytes"].as_str().unwrap();
+    let hash = Keccak256::digest(hex::decode(&txbytes[2..]).unwrap());
+    let london = &fixture["result"]["London"];
+    assert_eq!(london["hash"], json!(format!("0x{}", hex::encode(hash))));
+    assert_eq!(
+        london["sender"],
+        json!("0x76da6b3693efd723aa7e36d3ef41ac7663fb1af8")
+    );
+    assert_eq!(london["intrinsicGas"], json!("0x62d4"));
+    assert_eq!(fixture["result"]["Osaka"], *london);
+    assert!(fixture["result"].get("Prague").is_none());
+}
+
+#[test]
+fn it_verifies_vectors() {
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    cmd.arg("verify-vectors")
+        .arg("transactions/vectors")
+        .assert()
+        .success()
+        .stdout(contains(
+            "pass transactions/vectors/ttEIP1559/validTransfer.json validTransfer Prague",
+        ))
+        .stdout(contains("0 failed"));
+}
+
+#[test]
+fn it_fails_mismatched_vectors() {
+    let vectors = std::env::temp_dir().join("tx-util-vectors.json");
+    let valid = std::fs::read_to_string("transactions/vectors/ttEIP1559/validTransfer.json")
+        .unwrap()
+        .replace(
+            "\"London\": {",
+            "\"London\": {\"exception\": \"TR_IntrinsicGas\",",
+        );
+    std::fs::write(&vectors, valid).unwrap();
+
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    cmd.arg("verify-vectors")
+        .arg(&vectors)
+        .assert()
+        .failure()
+        .stdout(contains(
+            "validTransfer London: expected TR_IntrinsicGas, but the transaction is valid",
+        ))
+        .stdout(contains("4 passed, 1 failed, 0 skipped"));
+}
+
+#[test]
+fn it_inspects_rlp() {
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    cmd.args(["rlp", "inspect"])
+        .write_stdin(EIP_1559_CALL_HEX)
+        .assert()
+        .success()
+        .stdout(contains("type 2"))
+        .stdout(contains("maxPriorityFeePerGas: 0x"))
+        .stdout(contains("storageKeys: list of 1"));
+
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    cmd.args(["rlp", "inspect", "0xc6810a83646f67"])
+        .assert()
+        .success()
+        .stdout(contains("     1  81             1    0x0a (non-canonical)"));
+}
+
+#[test]
+fn it_encodes_and_decodes_rlp() {
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let assert = cmd
+        .args(["rlp", "encode", r#"["0x01", 0, [], ["0xdeadbeef", 1024]]"#])
+        .assert()
+        .success()
+        .stdout("0xcc0180c0c884deadbeef820400");
+
+    let encoded = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let output = cmd
+        .args(["rlp", "decode"])
+        .write_stdin(encoded)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let tree: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(tree, json!(["0x01", "0x", [], ["0xdeadbeef", "0x0400"]]));
+
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    cmd.args(["rlp", "decode", "0xc30102"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "the item at offset 0 has 3 bytes, but only 2 remain",
+        ));
+}
+
+#[test]
+fn it_signs_and_verifies_messages() {
+    let address = "0x76dA6B3693eFD723aA7e36d3Ef41aC7663fb1AF8";
+    let prehash = format!("0x{}", "ab".repeat(32));
+    for args in [
+        vec!["hello world"],
+        vec!["--scheme", "validator", "--validator", address, "hello"],
+        vec!["--scheme", "prehash", &prehash],
+        vec!["--hex", "0x68656c6c6f"],
+    ] {
+        let mut cmd = Command::cargo_bin("tx-util").unwrap();
+        let output = cmd
+            .arg("sign-message")
+            .args(["--signer", SIGNER])
+            .args(&args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let signed: Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(signed["signer"], json!(address.to_lowercase()));
+        let signature = signed["signature"].as_str().unwrap();
+        assert_eq!(signature.len(), 2 + 65 * 2);
+
+        let mut cmd = Command::cargo_bin("tx-util").unwrap();
+        cmd.arg("verify-message")
+            .args(["--signature", signature, "--address", address])
+            .args(&args)
+            .assert()
+            .success()
+            .stdout(contains(address));
+    }
+
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    let output = cmd
+        .arg("sign-message")
+        .args(["--signer", SIGNER])
+        .write_stdin("hello world")
+        .output()
+        .unwrap();
+    let signed: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        signed["hash"],
+        json!("0xd9eba16ed0ecae432b71fe008c98cc872bb4cc214d3220a36f365326cf807d68")
+    );
+
+    let mut cmd = Command::cargo_bin("tx-util").unwrap();
+    cmd.arg("verify-message")
+        .args(["--signature", signed["signature"].as_str().unwrap()])
+        .args(["--address", "0x0000000000000000000000000000000000000001"])
+        .arg("hello there")
+        .assert()
+        .failure()
+        .stderr(contains(
+            "not by 0x0000000000000000000000000000000000000001",
+        ));
+}
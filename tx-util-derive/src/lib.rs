@@ -0,0 +1,303 @@
+//! # tx-util-derive
+//!
+//! Derives the conversions between a struct and `crate::rlp::RlpItem` of
+//! tx-util. A struct is encoded as a list of its fields in order, each field
+//! converted with `Into`. `RlpEncodable` also derives
+//! `crate::rlp::Encodable`, which writes the same encoding without building
+//! the tree. `RlpDecodable` derives `crate::rlp::Decodable`, which decodes a
+//! borrowed view and fails instead of panicking.
+//!
+//! Field attributes:
+//! - `#[rlp(skip)]` is not encoded and decoded as `Default::default()`
+//! - `#[rlp(list)]` encodes an `Option` as a list of zero or one items, like
+//!   the draft EIP-7702 authorization `nonce`
+//! - `#[rlp(optional)]` encodes an `Option` only when it is `Some`, so it must
+//!   be followed by optional fields only
+//! - `#[rlp(flatten)]` appends the items of an `Option`, e.g. a signature
+//!   `[y_parity, r, s]`, to the list when it is `Some`. It must be the last
+//!   field, and its type decodes with `TryFrom<&[RlpView]>` and encodes
+//!   with the methods `encode_fields` and `fields_length`, which write its
+//!   items without a list header
+
+#![warn(
+    missing_docs,
+    non_ascii_idents,
+    unreachable_pub,
+    unused_crate_dependencies,
+    unused_results,
+    unused_qualifications,
+    nonstandard_style,
+    rustdoc::all
+)]
+#![deny(rust_2018_idioms, unsafe_code)]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Ident, Result};
+
+/// How a field is encoded
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Required,
+    Skip,
+    List,
+    Optional,
+    Flatten,
+}
+
+struct Field {
+    ident: Ident,
+    kind: Kind,
+}
+
+fn fields(input: &DeriveInput) -> Result<Vec<Field>> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(input, "only structs can be derived"));
+    };
+    let Fields::Named(named) = &data.fields else {
+        return Err(Error::new_spanned(
+            &data.fields,
+            "only structs with named fields can be derived",
+        ));
+    };
+
+    let mut fields = Vec::new();
+    for field in &named.named {
+        let mut kind = Kind::Required;
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("rlp")) {
+            attr.parse_nested_meta(|meta| {
+                kind = match meta.path.get_ident().map(Ident::to_string).as_deref() {
+                    Some("skip") => Kind::Skip,
+                    Some("list") => Kind::List,
+                    Some("optional") => Kind::Optional,
+                    Some("flatten") => Kind::Flatten,
+                    _ => return Err(meta.error("expected `skip`, `list`, `optional` or `flatten`")),
+                };
+                Ok(())
+            })?;
+        }
+        let ident = field.ident.clone().expect("named field");
+        fields.push(Field { ident, kind });
+    }
+
+    let encoded = fields.iter().filter(|f| f.kind != Kind::Skip);
+    let mut trailing = false;
+    for (i, field) in encoded.clone().enumerate() {
+        match field.kind {
+            Kind::Flatten if i + 1 != encoded.clone().count() => {
+                return Err(Error::new_spanned(
+                    &field.ident,
+                    "a `flatten` field must be the last field",
+                ))
+            }
+            Kind::Optional | Kind::Flatten => trailing = true,
+            _ if trailing => {
+                return Err(Error::new_spanned(
+                    &field.ident,
+                    "an `optional` field can only be followed by optional fields",
+                ))
+            }
+            _ => {}
+        }
+    }
+    Ok(fields)
+}
+
+/// Derives `From<Self> for RlpItem` and `Encodable`
+#[proc_macro_derive(RlpEncodable, attributes(rlp))]
+pub fn derive_encodable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    encodable(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Derives `Decodable`
+#[proc_macro_derive(RlpDecodable, attributes(rlp))]
+pub fn derive_decodable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    decodable(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn encodable(input: &DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let pushes = fields(input)?
+        .into_iter()
+        .map(|Field { ident, kind }| match kind {
+            Kind::Required => quote! {
+                items.push(value.#ident.into());
+            },
+            Kind::Skip => quote! {},
+            Kind::List => quote! {
+                items.push(crate::rlp::RlpItem::List(
+                    value.#ident.map(|v| vec![v.into()]).unwrap_or_default(),
+                ));
+            },
+            Kind::Optional => quote! {
+                if let Some(v) = value.#ident {
+                    items.push(v.into());
+                }
+            },
+            Kind::Flatten => quote! {
+                if let Some(v) = value.#ident {
+                    let mut rlp: Vec<crate::rlp::RlpItem> = v.into();
+                    items.append(&mut rlp);
+                }
+            },
+        });
+    let encodable = encodable_impl(input)?;
+    Ok(quote! {
+        impl From<#name> for crate::rlp::RlpItem {
+            fn from(value: #name) -> Self {
+                let mut items = Vec::new();
+                #(#pushes)*
+                crate::rlp::RlpItem::List(items)
+            }
+        }
+
+        #encodable
+    })
+}
+
+fn encodable_impl(input: &DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let fields = fields(input)?;
+    let lengths = fields.iter().map(|Field { ident, kind }| match kind {
+        Kind::Required => quote! {
+            payload += crate::rlp::Encodable::length(&self.#ident);
+        },
+        Kind::Skip => quote! {},
+        Kind::List => quote! {
+            let list = self.#ident.as_ref().map_or(0, crate::rlp::Encodable::length);
+            payload += crate::rlp::header_length(list) + list;
+        },
+        Kind::Optional => quote! {
+            if let Some(v) = &self.#ident {
+                payload += crate::rlp::Encodable::length(v);
+            }
+        },
+        Kind::Flatten => quote! {
+            if let Some(v) = &self.#ident {
+                payload += v.fields_length();
+            }
+        },
+    });
+    let encodes = fields.iter().map(|Field { ident, kind }| match kind {
+        Kind::Required => quote! {
+            crate::rlp::Encodable::encode(&self.#ident, out);
+        },
+        Kind::Skip => quote! {},
+        Kind::List => quote! {
+            let list = self.#ident.as_ref().map_or(0, crate::rlp::Encodable::length);
+            crate::rlp::encode_header(0xC0, list, out);
+            if let Some(v) = &self.#ident {
+                crate::rlp::Encodable::encode(v, out);
+            }
+        },
+        Kind::Optional => quote! {
+            if let Some(v) = &self.#ident {
+                crate::rlp::Encodable::encode(v, out);
+            }
+        },
+        Kind::Flatten => quote! {
+            if let Some(v) = &self.#ident {
+                v.encode_fields(out);
+            }
+        },
+    });
+    Ok(quote! {
+        impl crate::rlp::Encodable for #name {
+            fn encode(&self, out: &mut impl ::bytes::BufMut) {
+                crate::rlp::encode_header(0xC0, self.payload_length(), out);
+                #(#encodes)*
+            }
+
+            fn length(&self) -> usize {
+                let payload = self.payload_length();
+                crate::rlp::header_length(payload) + payload
+            }
+        }
+
+        impl #name {
+            /// The length of the encoded fields, without the list header
+            fn payload_length(&self) -> usize {
+                let mut payload = 0;
+                #(#lengths)*
+                payload
+            }
+        }
+    })
+}
+
+fn decodable(input: &DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let fields = fields(input)?;
+    let inits = fields.iter().map(|Field { ident, kind }| {
+        let missing = format!("missing field `{ident}` in the list at offset {{}}");
+        let next = quote! {
+            items
+                .next()
+                .ok_or_else(|| ::color_eyre::eyre::eyre!(#missing, view.offset))??
+        };
+        let value = match kind {
+            Kind::Required => quote! {
+                crate::rlp::Decodable::decode(#next)?
+            },
+            Kind::Skip => quote! {
+                Default::default()
+            },
+            Kind::List => {
+                let invalid = format!("`{ident}` must be a list of zero or one items");
+                quote! {
+                    {
+                        let field = #next;
+                        let mut list = crate::rlp::list(&field)?.items();
+                        let value = list
+                            .next()
+                            .transpose()?
+                            .map(crate::rlp::Decodable::decode)
+                            .transpose()?;
+                        if list.next().is_some() {
+                            ::color_eyre::eyre::bail!(#invalid);
+                        }
+                        value
+                    }
+                }
+            }
+            Kind::Optional => quote! {
+                items
+                    .next()
+                    .transpose()?
+                    .map(crate::rlp::Decodable::decode)
+                    .transpose()?
+            },
+            Kind::Flatten => quote! {
+                {
+                    let rest = items
+                        .by_ref()
+                        .collect::<::color_eyre::eyre::Result<Vec<_>>>()?;
+                    match rest.is_empty() {
+                        true => None,
+                        false => Some(::core::convert::TryFrom::try_from(rest.as_slice())?),
+                    }
+                }
+            },
+        };
+        quote! { #ident: #value, }
+    });
+    Ok(quote! {
+        impl crate::rlp::Decodable for #name {
+            fn decode(view: crate::rlp::RlpView<'_>) -> ::color_eyre::eyre::Result<Self> {
+                let mut items = crate::rlp::list(&view)?.items();
+                let decoded = #name { #(#inits)* };
+                if items.next().is_some() {
+                    ::color_eyre::eyre::bail!("too many fields in the list at offset {}", view.offset);
+                }
+                Ok(decoded)
+            }
+        }
+    })
+}